@@ -6,6 +6,7 @@
 -keep class me.sudodios.codewalker.core.LibCore {
     native <methods>;
 }
+-keep class me.sudodios.codewalker.core.*Exception { <init>(java.lang.String); }
 -keep class kotlinx.coroutines.** { *; }
 -keep class kotlinx.coroutines.swing.SwingDispatcherFactory
 -keep class kotlinx.serialization.** { *; }
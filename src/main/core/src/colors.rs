@@ -1,24 +1,27 @@
-use std::collections::HashMap;
+use std::str::FromStr;
 
-pub struct ColorFinder {
-    color_map : HashMap<String,String>
+use tokei::{Config, LanguageType};
+
+/// The color of the language stored under its display name `name`, for
+/// results saved by name such as snapshots, with the colors of `config`.
+pub fn color_of_name (config : &Config,name : &str) -> String {
+    LanguageType::from_str(name).map_or(LanguageType::DEFAULT_COLOR, |language| config.color(language)).to_string()
 }
 
-impl ColorFinder {
-    pub fn init () -> Self {
-        let mut color_map: HashMap<String, String> = HashMap::new();
-        let raw = include_str!("../rsc/langs-color-map.txt");
-        for line in raw.lines() {
-            let parts = line.splitn(2, ":").collect::<Vec<_>>();
-            let name = parts[0].trim().to_owned();
-            let hex_code = parts[1].trim().to_owned();
-            color_map.insert(name.to_lowercase(), hex_code);
-        }
-        Self {
-            color_map
-        }
-    }
-    pub fn get_color_by_lang_name (&self,name : &str) -> String {
-        self.color_map.get(name.to_lowercase().trim()).map(|value| value.clone()).unwrap_or_else(|| "#EDEDED".to_string())
+#[cfg(test)]
+mod tests {
+    use crate::project_config::ProjectConfig;
+
+    use super::*;
+
+    #[test]
+    fn finds_colors_by_display_name () {
+        let config = ProjectConfig {
+            colors: [("Rust".to_string(), "#b7410e".to_string())].into(),
+            ..ProjectConfig::default()
+        }.tokei_config();
+        assert_eq!(color_of_name(&config,"C++"), LanguageType::Cpp.color());
+        assert_eq!(color_of_name(&config,"rust"), "#b7410e");
+        assert_eq!(color_of_name(&config,"Not a language"), LanguageType::DEFAULT_COLOR);
     }
-}
\ No newline at end of file
+}
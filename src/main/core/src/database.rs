@@ -1,31 +1,114 @@
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
 use std::string::ToString;
-use rusqlite::Connection;
-use crate::models::ModelStatisticsDB;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+use rusqlite::{Connection, OptionalExtension, Row};
+use tokei::{CacheEntry, Config, FileStamp, LanguageType, ReportCache};
+use crate::colors::color_of_name;
+use crate::error::{CoreError, CoreResult};
+use crate::migrations;
+use crate::models::{ModelLangStats, ModelSnapshot, ModelStatisticsDB};
+use crate::project_config::ProjectConfig;
 
 const TABLE_NAME: &str = "projects";
-static mut DB_PATH: String = String::new();
-
-pub(crate) fn init_db (path : &str) {
-    unsafe {
-        DB_PATH = path.to_string();
-    }
-    let conn = Connection::open(path).unwrap();
-    conn.execute(format!("CREATE TABLE IF NOT EXISTS {} (
-                          id INTEGER PRIMARY KEY AUTOINCREMENT,
-                          name TEXT,
-                          root_folders TEXT,
-                          ignored_folders TEXT,
-                          last_update INTEGER,
-                          configs TEXT,
-                          analyze TEXT,
-                          totals TEXT)"
-                         ,TABLE_NAME).as_str(),()).unwrap();
-    conn.close().unwrap();
-}
-
-pub(crate) fn create_project (model_statistics_db: &ModelStatisticsDB) -> i64 {
-    let conn = Connection::open(unsafe { &DB_PATH }).unwrap();
-    conn.execute(format!("INSERT INTO {} (name,root_folders,ignored_folders,last_update,configs,analyze,totals)
+const SNAPSHOTS_TABLE_NAME: &str = "snapshots";
+const LANGUAGES_TABLE_NAME: &str = "snapshot_languages";
+const FILE_CACHE_TABLE_NAME: &str = "file_cache";
+const SNAPSHOT_COLUMNS: &str = "id,project_id,created_at,files,code,comments,docs,blanks,file_types,size_on_disk";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_IDLE_CONNECTIONS: usize = 4;
+
+static POOL: RwLock<Option<Arc<Pool>>> = RwLock::new(None);
+
+/// Connections to one database file, reused across JNI calls from any thread.
+struct Pool {
+    path : String,
+    idle : Mutex<Vec<Connection>>,
+}
+
+/// A connection borrowed from a [`Pool`], handed back to it on drop.
+struct PooledConnection {
+    conn : Option<Connection>,
+    pool : Arc<Pool>,
+}
+
+impl Pool {
+    /// Opens the database, migrating it to the latest schema.
+    fn open (path : &str) -> CoreResult<Arc<Self>> {
+        let mut conn = Self::connect(path)?;
+        migrations::migrate(&mut conn)?;
+        Ok(Arc::new(Self {
+            path: path.to_string(),
+            idle: Mutex::new(vec![conn]),
+        }))
+    }
+
+    fn connect (path : &str) -> CoreResult<Connection> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets readers run while a scan result is being written
+        conn.pragma_update(None,"journal_mode","WAL")?;
+        Ok(conn)
+    }
+
+    fn get (self : &Arc<Self>) -> CoreResult<PooledConnection> {
+        let idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner).pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => Self::connect(&self.path)?
+        };
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self.clone(),
+        })
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref (&self) -> &Connection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut (&mut self) -> &mut Connection {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledConnection {
+    // a poisoned lock still guards a valid list of idle connections, and
+    // panicking here could abort a thread that's already unwinding
+    fn drop (&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let mut idle = self.pool.idle.lock().unwrap_or_else(PoisonError::into_inner);
+            if idle.len() < MAX_IDLE_CONNECTIONS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+/// Opens `path` as the app database, closing the previous one if any.
+pub(crate) fn init_db (path : &str) -> CoreResult<()> {
+    let pool = Pool::open(path)?;
+    *POOL.write().unwrap() = Some(pool);
+    Ok(())
+}
+
+/// Closes the app database. Calls made before the next `init_db` fail, calls
+/// already running finish on their own connection.
+pub(crate) fn close_db () {
+    POOL.write().unwrap().take();
+}
+
+pub(crate) fn create_project (model_statistics_db: &ModelStatisticsDB) -> CoreResult<i64> {
+    let mut conn = connection()?;
+    let tx = conn.transaction()?;
+    tx.execute(format!("INSERT INTO {} (name,root_folders,ignored_folders,last_update,configs,analyze,totals)
                           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
          ,TABLE_NAME).as_str(),(
         &model_statistics_db.name,
@@ -35,15 +118,30 @@ pub(crate) fn create_project (model_statistics_db: &ModelStatisticsDB) -> i64 {
         &model_statistics_db.configs,
         &model_statistics_db.analyze,
         &model_statistics_db.totals,
-    )).unwrap();
-    let inserted_id = conn.last_insert_rowid();
-    conn.close().unwrap();
-    return inserted_id;
+    ))?;
+    let inserted_id = tx.last_insert_rowid();
+    insert_snapshot(&tx,inserted_id as usize,model_statistics_db.last_update,&model_statistics_db.analyze,&model_statistics_db.totals)?;
+    tx.commit()?;
+    Ok(inserted_id)
 }
 
-pub(crate) fn update_project (model_statistics_db: ModelStatisticsDB) {
-    let conn = Connection::open(unsafe { &DB_PATH }).unwrap();
-    conn.execute(format!("UPDATE {} set name=?1,root_folders=?2,ignored_folders=?3,last_update=?4,configs=?5,analyze=?6,totals=?7
+pub(crate) fn update_project (model_statistics_db: ModelStatisticsDB) -> CoreResult<()> {
+    let mut conn = connection()?;
+    let tx = conn.transaction()?;
+    store_project_update(&tx,&model_statistics_db)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Edits that leave the folders and scan settings alone, such as a rename or
+/// new colors, only rewrite the project row. Other edits also record a
+/// snapshot and drop the file cache, the next scan starts from scratch. A
+/// stored config that can't be read counts as changed, so saving repairs it.
+fn store_project_update (conn : &Connection,model_statistics_db : &ModelStatisticsDB) -> CoreResult<()> {
+    let previous = conn.query_row(format!("SELECT root_folders,ignored_folders,configs FROM {} WHERE id=?1",TABLE_NAME).as_str(),(&model_statistics_db.id,),|row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, ProjectConfig>(2).ok()))
+    }).optional()?;
+    let updated = conn.execute(format!("UPDATE {} set name=?1,root_folders=?2,ignored_folders=?3,last_update=?4,configs=?5,analyze=?6,totals=?7
                           WHERE id=?8"
                          ,TABLE_NAME).as_str(),(
         &model_statistics_db.name,
@@ -54,48 +152,430 @@ pub(crate) fn update_project (model_statistics_db: ModelStatisticsDB) {
         &model_statistics_db.analyze,
         &model_statistics_db.totals,
         &model_statistics_db.id
-    )).unwrap();
-    conn.close().unwrap();
+    ))?;
+    ensure_updated(updated,model_statistics_db.id)?;
+    let rescanned = previous.is_none_or(|(root_folders, ignored_folders, configs)| {
+        root_folders != model_statistics_db.root_folders
+            || ignored_folders != model_statistics_db.ignored_folders
+            || !configs.is_some_and(|configs| configs.scans_like(&model_statistics_db.configs))
+    });
+    if rescanned {
+        insert_snapshot(conn,model_statistics_db.id,model_statistics_db.last_update,&model_statistics_db.analyze,&model_statistics_db.totals)?;
+        conn.execute(format!("DELETE FROM {} WHERE project_id=?1",FILE_CACHE_TABLE_NAME).as_str(),(&model_statistics_db.id,))?;
+    }
+    Ok(())
 }
 
-pub(crate) fn refresh_analyze (p_id : usize,update_time : usize,languages : &str,totals : &str) {
-    let conn = Connection::open(unsafe { &DB_PATH }).unwrap();
-    conn.execute(format!("UPDATE {} set last_update=?1,analyze=?2,totals=?3 WHERE id=?4",TABLE_NAME).as_str(),(
+pub(crate) fn refresh_analyze (p_id : usize,update_time : usize,languages : &str,totals : &str) -> CoreResult<()> {
+    let mut conn = connection()?;
+    let tx = conn.transaction()?;
+    let updated = tx.execute(format!("UPDATE {} set last_update=?1,analyze=?2,totals=?3 WHERE id=?4",TABLE_NAME).as_str(),(
         &update_time,
         &languages,
         &totals,
         &p_id
-    )).unwrap();
-    conn.close().unwrap();
+    ))?;
+    ensure_updated(updated,p_id)?;
+    insert_snapshot(&tx,p_id,update_time,languages,totals)?;
+    tx.commit()?;
+    Ok(())
 }
 
-pub(crate) fn remove_project (p_id : usize) {
-    let conn = Connection::open(unsafe { &DB_PATH }).unwrap();
-    conn.execute(format!("DELETE FROM {} WHERE id={}",TABLE_NAME,p_id).as_str(),()).unwrap();
-    conn.close().unwrap();
+pub(crate) fn remove_project (p_id : usize) -> CoreResult<()> {
+    let mut conn = connection()?;
+    let tx = conn.transaction()?;
+    tx.execute(format!("DELETE FROM {} WHERE snapshot_id IN (SELECT id FROM {} WHERE project_id=?1)",LANGUAGES_TABLE_NAME,SNAPSHOTS_TABLE_NAME).as_str(),(&p_id,))?;
+    tx.execute(format!("DELETE FROM {} WHERE project_id=?1",SNAPSHOTS_TABLE_NAME).as_str(),(&p_id,))?;
+    tx.execute(format!("DELETE FROM {} WHERE project_id=?1",FILE_CACHE_TABLE_NAME).as_str(),(&p_id,))?;
+    tx.execute(format!("DELETE FROM {} WHERE id=?1",TABLE_NAME).as_str(),(&p_id,))?;
+    tx.commit()?;
+    Ok(())
 }
 
-pub(crate) fn read_projects () -> Vec<ModelStatisticsDB> {
-    let conn = Connection::open(unsafe { &DB_PATH }).unwrap();
+pub(crate) fn read_projects () -> CoreResult<Vec<ModelStatisticsDB>> {
+    let conn = connection()?;
     let mut result_out : Vec<ModelStatisticsDB> = Vec::new();
     {
-        let mut statment = conn.prepare(format!("SELECT * FROM {} ORDER BY last_update DESC",TABLE_NAME).as_str()).unwrap();
-        let result_iter = statment.query_map([], |row| {
-            Ok(ModelStatisticsDB {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                root_folders: row.get(2)?,
-                ignored_folders: row.get(3)?,
-                last_update: row.get(4)?,
-                configs: row.get(5)?,
-                analyze: row.get(6)?,
-                totals: row.get(7)?,
-            })
-        }).unwrap();
+        let mut statment = conn.prepare(format!("SELECT * FROM {} ORDER BY last_update DESC",TABLE_NAME).as_str())?;
+        let result_iter = statment.query_map([], row_to_project)?;
         for item in result_iter {
-            result_out.push(item.unwrap())
+            result_out.push(item?)
         }
     }
-    conn.close().unwrap();
-    return result_out
+    Ok(result_out)
+}
+
+pub(crate) fn read_project (p_id : usize) -> CoreResult<ModelStatisticsDB> {
+    let conn = connection()?;
+    conn.query_row(format!("SELECT * FROM {} WHERE id=?1",TABLE_NAME).as_str(),(&p_id,),row_to_project)
+        .optional()?
+        .ok_or_else(|| CoreError::InvalidArgument(format!("no project with id {}", p_id)))
+}
+
+fn row_to_project (row : &Row) -> rusqlite::Result<ModelStatisticsDB> {
+    Ok(ModelStatisticsDB {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        root_folders: row.get(2)?,
+        ignored_folders: row.get(3)?,
+        last_update: row.get(4)?,
+        configs: row.get(5)?,
+        analyze: row.get(6)?,
+        totals: row.get(7)?,
+    })
+}
+
+/// Lists every analysis run of a project, oldest first, without the per-language rows.
+pub(crate) fn read_snapshots (p_id : usize) -> CoreResult<Vec<ModelSnapshot>> {
+    let conn = connection()?;
+    let result_out = query_snapshots(&conn,p_id)?;
+    Ok(result_out)
+}
+
+pub(crate) fn read_snapshot (snapshot_id : usize) -> CoreResult<ModelSnapshot> {
+    let conn = connection()?;
+    let result_out = query_snapshot(&conn,snapshot_id)?;
+    Ok(result_out)
+}
+
+/// Stores one analysis run from the JSON blobs the app keeps on the project,
+/// `analyze` being its `ModelLangStats` array and `totals` its `Totals` object.
+fn insert_snapshot (conn : &Connection,p_id : usize,created_at : usize,analyze : &str,totals : &str) -> CoreResult<()> {
+    conn.execute(format!("INSERT INTO {} (project_id,created_at,files,code,comments,docs,blanks,file_types,size_on_disk)
+                          SELECT ?1,?2,
+                                 IFNULL(json_extract(?3,'$.totalFilesCount'),0),
+                                 IFNULL(json_extract(?3,'$.totalCodeLinesCount'),0),
+                                 IFNULL(json_extract(?3,'$.totalCommentLinesCount'),0),
+                                 IFNULL(json_extract(?3,'$.totalDocLinesCount'),0),
+                                 IFNULL(json_extract(?3,'$.totalBlankLinesCount'),0),
+                                 IFNULL(json_extract(?3,'$.totalFileTypesCount'),0),
+                                 IFNULL(json_extract(?3,'$.sizeOnDisk'),0)"
+                         ,SNAPSHOTS_TABLE_NAME).as_str(),(&p_id,&created_at,&totals))?;
+    let snapshot_id = conn.last_insert_rowid();
+    conn.execute(format!("INSERT OR IGNORE INTO {} (snapshot_id,name,files,lines,code,comments,docs,blanks,complexity)
+                          SELECT ?1,json_extract(value,'$.name'),
+                                 IFNULL(json_extract(value,'$.filesCount'),0),
+                                 IFNULL(json_extract(value,'$.totalLinesCount'),0),
+                                 IFNULL(json_extract(value,'$.codeLinesCount'),0),
+                                 IFNULL(json_extract(value,'$.commentLinesCount'),0),
+                                 IFNULL(json_extract(value,'$.docLinesCount'),0),
+                                 IFNULL(json_extract(value,'$.blankLinesCount'),0),
+                                 IFNULL(json_extract(value,'$.complexity'),0)
+                          FROM json_each(?2) WHERE json_extract(value,'$.name') IS NOT NULL"
+                         ,LANGUAGES_TABLE_NAME).as_str(),(&snapshot_id,&analyze))?;
+    Ok(())
+}
+
+fn query_snapshots (conn : &Connection,p_id : usize) -> CoreResult<Vec<ModelSnapshot>> {
+    let mut statment = conn.prepare(format!("SELECT {} FROM {} WHERE project_id=?1 ORDER BY created_at,id",SNAPSHOT_COLUMNS,SNAPSHOTS_TABLE_NAME).as_str())?;
+    let result_iter = statment.query_map((&p_id,),row_to_snapshot)?;
+    let mut result_out : Vec<ModelSnapshot> = Vec::new();
+    for item in result_iter {
+        result_out.push(item?)
+    }
+    Ok(result_out)
+}
+
+/// The settings of a project, the defaults if it no longer exists.
+fn query_project_config (conn : &Connection,p_id : usize) -> CoreResult<ProjectConfig> {
+    let config = conn.query_row(format!("SELECT configs FROM {} WHERE id=?1",TABLE_NAME).as_str(),(&p_id,),|row| row.get(0)).optional()?;
+    Ok(config.unwrap_or_default())
+}
+
+fn query_snapshot (conn : &Connection,snapshot_id : usize) -> CoreResult<ModelSnapshot> {
+    let mut snapshot = conn.query_row(format!("SELECT {} FROM {} WHERE id=?1",SNAPSHOT_COLUMNS,SNAPSHOTS_TABLE_NAME).as_str(),(&snapshot_id,),row_to_snapshot)
+        .optional()?
+        .ok_or_else(|| CoreError::InvalidArgument(format!("no snapshot with id {}", snapshot_id)))?;
+    let config = query_project_config(conn,snapshot.projectId)?.tokei_config();
+    let mut statment = conn.prepare(format!("SELECT name,files,lines,code,comments,docs,blanks,complexity FROM {} WHERE snapshot_id=?1 ORDER BY code DESC,name",LANGUAGES_TABLE_NAME).as_str())?;
+    let result_iter = statment.query_map((&snapshot_id,), |row| {
+        let name : String = row.get(0)?;
+        Ok(ModelLangStats {
+            color: color_of_name(&config,&name),
+            name,
+            filesCount: row.get(1)?,
+            totalLinesCount: row.get(2)?,
+            codeLinesCount: row.get(3)?,
+            commentLinesCount: row.get(4)?,
+            docLinesCount: row.get(5)?,
+            blankLinesCount: row.get(6)?,
+            complexity: row.get(7)?,
+        })
+    })?;
+    for item in result_iter {
+        snapshot.languages.push(item?)
+    }
+    Ok(snapshot)
+}
+
+fn row_to_snapshot (row : &Row) -> rusqlite::Result<ModelSnapshot> {
+    Ok(ModelSnapshot {
+        id: row.get(0)?,
+        projectId: row.get(1)?,
+        createdAt: row.get(2)?,
+        totalFilesCount: row.get(3)?,
+        totalCodeLinesCount: row.get(4)?,
+        totalCommentLinesCount: row.get(5)?,
+        totalDocLinesCount: row.get(6)?,
+        totalBlankLinesCount: row.get(7)?,
+        totalFileTypesCount: row.get(8)?,
+        sizeOnDisk: row.get(9)?,
+        languages: Vec::new(),
+    })
+}
+
+/// The per-file results of the project's last scan, for a rescan with `config`
+/// that also compares file contents if `content_hash` is set.
+pub(crate) fn read_file_cache (p_id : usize,config : &Config,content_hash : bool) -> CoreResult<ReportCache> {
+    let conn = connection()?;
+    query_file_cache(&conn,p_id,config,content_hash)
+}
+
+/// Replaces the stored per-file results of the project with `cache`.
+pub(crate) fn write_file_cache (p_id : usize,cache : &ReportCache) -> CoreResult<()> {
+    let mut conn = connection()?;
+    let tx = conn.transaction()?;
+    store_file_cache(&tx,p_id,cache)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn query_file_cache (conn : &Connection,p_id : usize,config : &Config,content_hash : bool) -> CoreResult<ReportCache> {
+    let cache = ReportCache::new(config).with_content_hash(content_hash);
+    let mut statment = conn.prepare(format!("SELECT path,language,modified,size,hash,stats,class FROM {} WHERE project_id=?1",FILE_CACHE_TABLE_NAME).as_str())?;
+    let mut rows = statment.query((&p_id,))?;
+    while let Some(row) = rows.next()? {
+        let path : String = row.get(0)?;
+        let language : String = row.get(1)?;
+        let stats : String = row.get(5)?;
+        let class : String = row.get(6)?;
+        // rows of languages or formats this version no longer knows are reparsed
+        let (Ok(language), Ok(stats), Ok(class)) = (language.parse::<LanguageType>(), serde_json::from_str(&stats), serde_json::from_str(&class)) else {
+            continue
+        };
+        cache.insert(PathBuf::from(path),CacheEntry {
+            language,
+            stamp: FileStamp {
+                modified: row.get::<_, i64>(2)? as u64,
+                size: row.get::<_, i64>(3)? as u64,
+                hash: row.get::<_, Option<i64>>(4)?.map(|h| h as u64),
+            },
+            stats,
+            class
+        });
+    }
+    Ok(cache)
+}
+
+fn store_file_cache (conn : &Connection,p_id : usize,cache : &ReportCache) -> CoreResult<()> {
+    conn.execute(format!("DELETE FROM {} WHERE project_id=?1",FILE_CACHE_TABLE_NAME).as_str(),(&p_id,))?;
+    let mut statment = conn.prepare(format!("INSERT INTO {} (project_id,path,language,modified,size,hash,stats,class)
+                                             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",FILE_CACHE_TABLE_NAME).as_str())?;
+    for (path, entry) in cache.entries() {
+        statment.execute((
+            &p_id,
+            path.to_string_lossy(),
+            entry.language.name(),
+            entry.stamp.modified as i64,
+            entry.stamp.size as i64,
+            entry.stamp.hash.map(|h| h as i64),
+            serde_json::to_string(&entry.stats)?,
+            serde_json::to_string(&entry.class)?,
+        ))?;
+    }
+    Ok(())
+}
+
+fn connection () -> CoreResult<PooledConnection> {
+    let pool = POOL.read().unwrap().clone()
+        .ok_or_else(|| CoreError::InvalidArgument("initDB must be called before using the database".to_string()))?;
+    pool.get()
+}
+
+fn ensure_updated (updated : usize,p_id : usize) -> CoreResult<()> {
+    if updated == 0 {
+        return Err(CoreError::InvalidArgument(format!("no project with id {}", p_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated () -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn snapshots_keep_every_run_with_languages () {
+        let conn = migrated();
+        insert_snapshot(&conn,7,1000,
+                        r#"[{"name":"Rust","filesCount":2,"totalLinesCount":30,"codeLinesCount":20,"commentLinesCount":6,"blankLinesCount":4}]"#,
+                        r#"{"totalFilesCount":2,"totalCodeLinesCount":20,"totalCommentLinesCount":6,"totalBlankLinesCount":4,"totalFileTypesCount":1,"sizeOnDisk":512}"#).unwrap();
+        insert_snapshot(&conn,7,2000,
+                        r#"[{"name":"Rust","filesCount":3,"totalLinesCount":50,"codeLinesCount":35,"commentLinesCount":9,"docLinesCount":5,"blankLinesCount":6,"complexity":12},
+                            {"name":"TOML","filesCount":1,"totalLinesCount":10,"codeLinesCount":8,"commentLinesCount":0,"blankLinesCount":2}]"#,
+                        r#"{"totalFilesCount":4,"totalCodeLinesCount":43,"totalCommentLinesCount":9,"totalDocLinesCount":5,"totalBlankLinesCount":8,"totalFileTypesCount":2,"sizeOnDisk":900}"#).unwrap();
+        insert_snapshot(&conn,8,1500,"[]","{}").unwrap();
+
+        let snapshots = query_snapshots(&conn,7).unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.createdAt).collect::<Vec<_>>(), vec![1000, 2000]);
+        assert_eq!(snapshots[1].totalCodeLinesCount, 43);
+        assert_eq!((snapshots[0].totalDocLinesCount, snapshots[1].totalDocLinesCount), (0, 5));
+        assert!(snapshots[1].languages.is_empty());
+
+        let latest = query_snapshot(&conn,snapshots[1].id).unwrap();
+        assert_eq!(latest.sizeOnDisk, 900);
+        assert_eq!(latest.languages.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(), vec!["Rust", "TOML"]);
+        assert_eq!(latest.languages[0].codeLinesCount, 35);
+        assert_eq!((latest.languages[0].complexity, latest.languages[1].complexity), (12, 0));
+        assert_eq!((latest.languages[0].docLinesCount, latest.languages[1].docLinesCount), (5, 0));
+    }
+
+    #[test]
+    fn snapshots_are_colored_with_the_project_colors () {
+        let conn = migrated();
+        let configs = ProjectConfig { colors: [("Rust".to_string(), "#b7410e".to_string())].into(), ..ProjectConfig::default() };
+        conn.execute(format!("INSERT INTO {} (id,name,root_folders,ignored_folders,last_update,configs,analyze,totals) VALUES (7,'p','[]','[]',0,?1,'[]','{{}}')",TABLE_NAME).as_str(),(&configs,)).unwrap();
+        insert_snapshot(&conn,7,1000,r#"[{"name":"Rust","codeLinesCount":20},{"name":"TOML","codeLinesCount":8}]"#,"{}").unwrap();
+
+        let snapshot = query_snapshot(&conn,query_snapshots(&conn,7).unwrap()[0].id).unwrap();
+
+        assert_eq!(snapshot.languages.iter().map(|l| l.color.as_str()).collect::<Vec<_>>(), vec!["#b7410e", LanguageType::Toml.color()]);
+    }
+
+    #[test]
+    fn pool_reuses_connections_across_threads () {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code_walker.db");
+        let pool = Pool::open(path.to_str().unwrap()).unwrap();
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let pool = &pool;
+                scope.spawn(move || {
+                    let conn = pool.get().unwrap();
+                    insert_snapshot(&conn,i,i * 1000,"[]","{}").unwrap();
+                });
+            }
+        });
+
+        let conn = pool.get().unwrap();
+        let count : usize = conn.query_row("SELECT COUNT(*) FROM snapshots",[],|row| row.get(0)).unwrap();
+        assert_eq!(count, 8);
+        let journal_mode : String = conn.pragma_query_value(None,"journal_mode",|row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+        assert!(pool.idle.lock().unwrap().len() <= MAX_IDLE_CONNECTIONS);
+    }
+
+    #[test]
+    fn pool_outlives_a_poisoned_lock () {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code_walker.db");
+        let pool = Pool::open(path.to_str().unwrap()).unwrap();
+        let conn = pool.get().unwrap();
+
+        let _ = std::thread::scope(|scope| scope.spawn(|| {
+            let _idle = pool.idle.lock().unwrap();
+            panic!("poisons the pool");
+        }).join());
+        assert!(pool.idle.is_poisoned());
+
+        drop(conn);
+        let conn = pool.get().unwrap();
+        let count : usize = conn.query_row("SELECT COUNT(*) FROM snapshots",[],|row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn file_cache_round_trips_per_project () {
+        let conn = migrated();
+        let config = Config::default();
+        let cache = ReportCache::new(&config);
+        let mut stats = tokei::CodeStats::new();
+        stats.code = 12;
+        stats.blanks = 3;
+        cache.insert(PathBuf::from("/src/main.rs"),CacheEntry {
+            language: LanguageType::Rust,
+            stamp: FileStamp { modified: 1_700_000_000_000_000_000, size: 256, hash: Some(u64::MAX) },
+            stats: stats.clone(),
+            class: Some(tokei::FileClass::Generated)
+        });
+        store_file_cache(&conn,3,&cache).unwrap();
+        store_file_cache(&conn,4,&ReportCache::new(&config)).unwrap();
+
+        let restored = query_file_cache(&conn,3,&config,true).unwrap().entries();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].0, PathBuf::from("/src/main.rs"));
+        assert_eq!(restored[0].1.stamp.hash, Some(u64::MAX));
+        assert_eq!(restored[0].1.stats, stats);
+        assert_eq!(restored[0].1.class, Some(tokei::FileClass::Generated));
+        assert!(query_file_cache(&conn,4,&config,false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_scan_changes_snapshot_and_drop_the_file_cache () {
+        let conn = migrated();
+        let mut project = ModelStatisticsDB {
+            id: 7,
+            name: "p".to_string(),
+            root_folders: r#"["/work/p"]"#.to_string(),
+            ignored_folders: "[]".to_string(),
+            last_update: 1000,
+            configs: ProjectConfig::default(),
+            analyze: "[]".to_string(),
+            totals: "{}".to_string(),
+        };
+        conn.execute(format!("INSERT INTO {} (id,name,root_folders,ignored_folders,last_update,configs,analyze,totals) VALUES (7,'p',?1,'[]',1000,?2,'[]','{{}}')",TABLE_NAME).as_str(),(&project.root_folders,&project.configs)).unwrap();
+        let cache = ReportCache::new(&Config::default());
+        cache.insert(PathBuf::from("/work/p/main.rs"),CacheEntry {
+            language: LanguageType::Rust,
+            stamp: FileStamp { modified: 1, size: 2, hash: None },
+            stats: tokei::CodeStats::new(),
+            class: None
+        });
+        store_file_cache(&conn,7,&cache).unwrap();
+
+        project.name = "renamed".to_string();
+        project.configs.colors.insert("Rust".to_string(),"#b7410e".to_string());
+        store_project_update(&conn,&project).unwrap();
+        assert!(query_snapshots(&conn,7).unwrap().is_empty());
+        assert_eq!(query_file_cache(&conn,7,&Config::default(),false).unwrap().len(), 1);
+
+        project.configs.hidden = true;
+        store_project_update(&conn,&project).unwrap();
+        assert_eq!(query_snapshots(&conn,7).unwrap().len(), 1);
+        assert!(query_file_cache(&conn,7,&Config::default(),false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn saving_over_an_unreadable_config_repairs_it () {
+        let conn = migrated();
+        conn.execute(format!("INSERT INTO {} (id,name,root_folders,ignored_folders,last_update,configs,analyze,totals) VALUES (7,'p','[]','[]',1000,'not json','[]','{{}}')",TABLE_NAME).as_str(),()).unwrap();
+        let project = ModelStatisticsDB {
+            id: 7,
+            name: "p".to_string(),
+            root_folders: "[]".to_string(),
+            ignored_folders: "[]".to_string(),
+            last_update: 1000,
+            configs: ProjectConfig::default(),
+            analyze: "[]".to_string(),
+            totals: "{}".to_string(),
+        };
+
+        store_project_update(&conn,&project).unwrap();
+
+        assert_eq!(query_snapshots(&conn,7).unwrap().len(), 1);
+        let configs : ProjectConfig = conn.query_row(format!("SELECT configs FROM {} WHERE id=7",TABLE_NAME).as_str(),(),|row| row.get(0)).unwrap();
+        assert_eq!(configs, ProjectConfig::default());
+    }
+
+    #[test]
+    fn missing_snapshot_is_an_invalid_argument () {
+        let conn = migrated();
+        assert!(matches!(query_snapshot(&conn,42), Err(CoreError::InvalidArgument(_))));
+    }
 }
\ No newline at end of file
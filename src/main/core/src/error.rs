@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use jni::JNIEnv;
+
+const DB_EXCEPTION: &str = "me/sudodios/codewalker/core/DbException";
+const IO_EXCEPTION: &str = "me/sudodios/codewalker/core/IoException";
+const INVALID_ARGUMENT_EXCEPTION: &str = "me/sudodios/codewalker/core/InvalidArgumentException";
+const CORE_EXCEPTION: &str = "me/sudodios/codewalker/core/CoreException";
+
+/// Every failure the core lib can report back to Kotlin.
+#[derive(Debug)]
+pub enum CoreError {
+    Db(rusqlite::Error),
+    Io(io::Error),
+    InvalidArgument(String),
+    Jni(jni::errors::Error),
+    Panic(String),
+}
+
+pub type CoreResult<T> = Result<T, CoreError>;
+
+impl CoreError {
+    /// The Kotlin exception class thrown for this error.
+    fn exception_class (&self) -> &'static str {
+        match self {
+            CoreError::Db(_) => DB_EXCEPTION,
+            CoreError::Io(_) => IO_EXCEPTION,
+            CoreError::InvalidArgument(_) => INVALID_ARGUMENT_EXCEPTION,
+            CoreError::Jni(_) | CoreError::Panic(_) => CORE_EXCEPTION,
+        }
+    }
+
+    pub fn from_panic (payload : Box<dyn Any + Send>) -> Self {
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        CoreError::Panic(message)
+    }
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoreError::Db(e) => write!(f, "database error: {}", e),
+            CoreError::Io(e) => write!(f, "io error: {}", e),
+            CoreError::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+            CoreError::Jni(e) => write!(f, "jni error: {}", e),
+            CoreError::Panic(message) => write!(f, "core panicked: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CoreError {}
+
+impl From<rusqlite::Error> for CoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        CoreError::Db(e)
+    }
+}
+
+impl From<io::Error> for CoreError {
+    fn from(e: io::Error) -> Self {
+        CoreError::Io(e)
+    }
+}
+
+impl From<fs_extra::error::Error> for CoreError {
+    fn from(e: fs_extra::error::Error) -> Self {
+        CoreError::Io(io::Error::other(e.to_string()))
+    }
+}
+
+impl From<serde_json::Error> for CoreError {
+    fn from(e: serde_json::Error) -> Self {
+        CoreError::InvalidArgument(format!("malformed json: {}", e))
+    }
+}
+
+impl From<jni::errors::Error> for CoreError {
+    fn from(e: jni::errors::Error) -> Self {
+        match e {
+            jni::errors::Error::NullPtr(name) | jni::errors::Error::NullDeref(name) => {
+                CoreError::InvalidArgument(format!("{} must not be null", name))
+            }
+            e => CoreError::Jni(e),
+        }
+    }
+}
+
+/// Runs the body of a `Java_me_sudodios_*` function, turning both returned
+/// errors and panics into a Kotlin exception instead of letting them cross
+/// the FFI boundary. `fallback` is handed back to the JVM, which ignores it
+/// because an exception is pending.
+pub fn jni_call<'local, T, F> (env : &mut JNIEnv<'local>, fallback : T, body : F) -> T
+    where F: FnOnce(&mut JNIEnv<'local>) -> CoreResult<T> {
+    let result = catch_unwind(AssertUnwindSafe(|| body(env))).unwrap_or_else(|payload| Err(CoreError::from_panic(payload)));
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            // a Java exception raised during the call already describes the failure best
+            if !env.exception_check().unwrap_or(false) {
+                let _ = env.throw_new(error.exception_class(), error.to_string());
+            }
+            fallback
+        }
+    }
+}
@@ -1,96 +1,323 @@
 use jni::JNIEnv;
 use jni::objects::{JObject, JObjectArray, JString, JValue};
-use jni::sys::{jint, jlong, jsize};
-use crate::models::{ModelStatistics, ModelStatisticsDB};
+use jni::sys::{jboolean, jint, jlong, jsize};
+use crate::error::{CoreError, CoreResult};
+use crate::project_config::ProjectConfig;
+use crate::models::{ModelAuthorStats, ModelBlame, ModelCategoryStats, ModelDirStats, ModelFilePage, ModelLangStats, ModelScanProgress, ModelSnapshot, ModelSnapshotDiff, ModelStatistics, ModelStatisticsDB};
 
-pub(crate) fn convert_data_class_to_model_db(env: &mut JNIEnv, input : &JObject) -> ModelStatisticsDB {
-    let id: jlong = env.get_field(input, "id", "J").unwrap().j().unwrap();
-    let name: JString = JString::from(env.get_field(input, "name", "Ljava/lang/String;").unwrap().l().unwrap());
-    let root_folders: JString = JString::from(env.get_field(input, "root_folders", "Ljava/lang/String;").unwrap().l().unwrap());
-    let ignored_folders: JString = JString::from(env.get_field(input, "ignored_folders", "Ljava/lang/String;").unwrap().l().unwrap());
-    let last_update: jlong = env.get_field(input, "last_update", "J").unwrap().j().unwrap();
-    let configs: JString = JString::from(env.get_field(input, "configs", "Ljava/lang/String;").unwrap().l().unwrap());
-    let analyze: JString = JString::from(env.get_field(input, "analyze", "Ljava/lang/String;").unwrap().l().unwrap());
-    let totals: JString = JString::from(env.get_field(input, "totals", "Ljava/lang/String;").unwrap().l().unwrap());
-    let _name : String = env.get_string(&name).expect("").into();
-    let _root_folders : String = env.get_string(&root_folders).expect("").into();
-    let _ignored_folders : String = env.get_string(&ignored_folders).expect("").into();
-    let _configs : String = env.get_string(&configs).expect("").into();
-    let _analyze : String = env.get_string(&analyze).expect("").into();
-    let _totals : String = env.get_string(&totals).expect("").into();
-    return ModelStatisticsDB {
+pub(crate) fn convert_data_class_to_model_db(env: &mut JNIEnv, input : &JObject) -> CoreResult<ModelStatisticsDB> {
+    let id: jlong = env.get_field(input, "id", "J")?.j()?;
+    let name: JString = JString::from(env.get_field(input, "name", "Ljava/lang/String;")?.l()?);
+    let root_folders: JString = JString::from(env.get_field(input, "root_folders", "Ljava/lang/String;")?.l()?);
+    let ignored_folders: JString = JString::from(env.get_field(input, "ignored_folders", "Ljava/lang/String;")?.l()?);
+    let last_update: jlong = env.get_field(input, "last_update", "J")?.j()?;
+    let configs: JString = JString::from(env.get_field(input, "configs", "Ljava/lang/String;")?.l()?);
+    let analyze: JString = JString::from(env.get_field(input, "analyze", "Ljava/lang/String;")?.l()?);
+    let totals: JString = JString::from(env.get_field(input, "totals", "Ljava/lang/String;")?.l()?);
+    let _name : String = env.get_string(&name)?.into();
+    let _root_folders : String = env.get_string(&root_folders)?.into();
+    let _ignored_folders : String = env.get_string(&ignored_folders)?.into();
+    let _configs : String = env.get_string(&configs)?.into();
+    let _analyze : String = env.get_string(&analyze)?.into();
+    let _totals : String = env.get_string(&totals)?.into();
+    return Ok(ModelStatisticsDB {
         id : id as usize,
         name: _name,
         root_folders:_root_folders,
         ignored_folders: _ignored_folders,
         last_update: last_update as usize,
-        configs: _configs,
+        configs: ProjectConfig::from_json(&_configs)?,
         analyze: _analyze,
         totals: _totals
-    }
+    })
 }
 
-pub(crate) fn convert_vec_to_j_o_array (mut env : JNIEnv,input_vec : Vec<ModelStatisticsDB>) -> JObjectArray {
-    let kt_model_db_stats = env.find_class("me/sudodios/codewalker/models/ModelStatisticsDB").unwrap();
-    let array = env.new_object_array(input_vec.len() as i32, &kt_model_db_stats, JObject::null()).unwrap();
+pub(crate) fn convert_vec_to_j_o_array<'local> (env : &mut JNIEnv<'local>,input_vec : Vec<ModelStatisticsDB>) -> CoreResult<JObjectArray<'local>> {
+    let kt_model_db_stats = env.find_class("me/sudodios/codewalker/models/ModelStatisticsDB")?;
+    let array = env.new_object_array(input_vec.len() as i32, &kt_model_db_stats, JObject::null())?;
     for (i, item) in input_vec.iter().enumerate() {
         let array_ref = &array;
         let item_obj = env.new_object(&kt_model_db_stats,
                                       "(JLjava/lang/String;Ljava/lang/String;Ljava/lang/String;JLjava/lang/String;Ljava/lang/String;Ljava/lang/String;)V",
                                       &[
                                           JValue::Long(item.id as jlong),
-                                          JValue::Object(&*env.new_string(item.name.clone()).unwrap()),
-                                          JValue::Object(&*env.new_string(item.root_folders.clone()).unwrap()),
-                                          JValue::Object(&*env.new_string(item.ignored_folders.clone()).unwrap()),
+                                          JValue::Object(&*env.new_string(item.name.clone())?),
+                                          JValue::Object(&*env.new_string(item.root_folders.clone())?),
+                                          JValue::Object(&*env.new_string(item.ignored_folders.clone())?),
                                           JValue::Long(item.last_update as jlong),
-                                          JValue::Object(&*env.new_string(item.configs.clone()).unwrap()),
-                                          JValue::Object(&*env.new_string(item.analyze.clone()).unwrap()),
-                                          JValue::Object(&*env.new_string(item.totals.clone()).unwrap())
+                                          JValue::Object(&*env.new_string(item.configs.to_json())?),
+                                          JValue::Object(&*env.new_string(item.analyze.clone())?),
+                                          JValue::Object(&*env.new_string(item.totals.clone())?)
                                       ]
-        ).unwrap();
-        env.set_object_array_element(array_ref, i as i32, item_obj).unwrap();
+        )?;
+        env.set_object_array_element(array_ref, i as i32, item_obj)?;
     }
-    array
+    Ok(array)
 }
 
-pub(crate) fn convert_java_array_to_vec (env: &mut JNIEnv, input : &JObjectArray) -> Vec<String> {
-    let length = env.get_array_length(input).unwrap() as usize;
+pub(crate) fn convert_java_array_to_vec (env: &mut JNIEnv, input : &JObjectArray) -> CoreResult<Vec<String>> {
+    let length = env.get_array_length(input)? as usize;
     let mut strings: Vec<String> = Vec::with_capacity(length);
     for i in 0..length {
-        let j_object = env.get_object_array_element(input,i as jsize).unwrap();
+        let j_object = env.get_object_array_element(input,i as jsize)?;
         let j_str = JString::from(j_object);
-        let input: String = env.get_string(&j_str).unwrap().into();
+        let input: String = env.get_string(&j_str)?.into();
         strings.push(input);
     }
-    strings
+    Ok(strings)
 }
 
-pub(crate) fn convert_model_stat_to_object (mut env: JNIEnv, model_statistics: ModelStatistics) -> JObject {
-    let kt_lang_stat_class = env.find_class("me/sudodios/codewalker/models/ModelLangStats").unwrap();
-    let array = env.new_object_array(model_statistics.languages.len() as i32, &kt_lang_stat_class, JObject::null()).unwrap();
-    for (i, lang) in model_statistics.languages.iter().enumerate() {
+fn convert_lang_stats_to_array<'local> (env: &mut JNIEnv<'local>, languages : &[ModelLangStats]) -> CoreResult<JObjectArray<'local>> {
+    let kt_lang_stat_class = env.find_class("me/sudodios/codewalker/models/ModelLangStats")?;
+    let array = env.new_object_array(languages.len() as i32, &kt_lang_stat_class, JObject::null())?;
+    for (i, lang) in languages.iter().enumerate() {
         let array_ref = &array;
-        let kt_lang_stat = env.new_object(&kt_lang_stat_class, "(Ljava/lang/String;Ljava/lang/String;IIIII)V", &[
-            JValue::Object(&*env.new_string(lang.name.clone()).unwrap()),
-            JValue::Object(&*env.new_string(lang.color.clone()).unwrap()),
+        let kt_lang_stat = env.new_object(&kt_lang_stat_class, "(Ljava/lang/String;Ljava/lang/String;IIIIIII)V", &[
+            JValue::Object(&*env.new_string(lang.name.clone())?),
+            JValue::Object(&*env.new_string(lang.color.clone())?),
             JValue::Int(lang.filesCount as jint),
             JValue::Int(lang.totalLinesCount as jint),
             JValue::Int(lang.codeLinesCount as jint),
             JValue::Int(lang.commentLinesCount as jint),
+            JValue::Int(lang.docLinesCount as jint),
             JValue::Int(lang.blankLinesCount as jint),
-        ]).unwrap();
-        env.set_object_array_element(array_ref, i as i32, kt_lang_stat).unwrap();
+            JValue::Int(lang.complexity as jint),
+        ])?;
+        env.set_object_array_element(array_ref, i as i32, kt_lang_stat)?;
     }
-    let kt_model_stats = env.find_class("me/sudodios/codewalker/models/ModelStatisticsNative").unwrap();
-    let kt_model_obj = env.new_object(kt_model_stats, "(JJJJJJ[Lme/sudodios/codewalker/models/ModelLangStats;J)V", &[
+    Ok(array)
+}
+
+fn convert_category_stats_to_array<'local> (env: &mut JNIEnv<'local>, categories : &[ModelCategoryStats]) -> CoreResult<JObjectArray<'local>> {
+    let kt_category_stat_class = env.find_class("me/sudodios/codewalker/models/ModelCategoryStats")?;
+    let array = env.new_object_array(categories.len() as i32, &kt_category_stat_class, JObject::null())?;
+    for (i, category) in categories.iter().enumerate() {
+        let kt_category_stat = env.new_object(&kt_category_stat_class, "(Ljava/lang/String;IIIIII)V", &[
+            JValue::Object(&*env.new_string(category.name.clone())?),
+            JValue::Int(category.filesCount as jint),
+            JValue::Int(category.totalLinesCount as jint),
+            JValue::Int(category.codeLinesCount as jint),
+            JValue::Int(category.commentLinesCount as jint),
+            JValue::Int(category.docLinesCount as jint),
+            JValue::Int(category.blankLinesCount as jint),
+        ])?;
+        env.set_object_array_element(&array, i as i32, kt_category_stat)?;
+    }
+    Ok(array)
+}
+
+pub(crate) fn convert_model_stat_to_object<'local> (env: &mut JNIEnv<'local>, model_statistics: ModelStatistics) -> CoreResult<JObject<'local>> {
+    let array = convert_lang_stats_to_array(env, &model_statistics.languages)?;
+    let categories = convert_category_stats_to_array(env, &model_statistics.categories)?;
+    let cache_error = match &model_statistics.cacheError {
+        Some(error) => JObject::from(env.new_string(error)?),
+        None => JObject::null()
+    };
+    let kt_model_stats = env.find_class("me/sudodios/codewalker/models/ModelStatisticsNative")?;
+    let kt_model_obj = env.new_object(kt_model_stats, "(JJJJJJJ[Lme/sudodios/codewalker/models/ModelLangStats;[Lme/sudodios/codewalker/models/ModelCategoryStats;JZLjava/lang/String;)V", &[
         JValue::Long(model_statistics.totalFilesCount as jlong),
         JValue::Long(model_statistics.totalCodeLinesCount as jlong),
         JValue::Long(model_statistics.totalCommentLinesCount as jlong),
+        JValue::Long(model_statistics.totalDocLinesCount as jlong),
         JValue::Long(model_statistics.totalBlankLinesCount as jlong),
         JValue::Long(model_statistics.totalFileTypesCount as jlong),
         JValue::Long(model_statistics.sizeOnDisk as jlong),
         JValue::Object(&*array),
+        JValue::Object(&*categories),
         JValue::Long(model_statistics.lastUpdateTime.clone() as jlong),
-    ]).unwrap();
-    kt_model_obj
+        JValue::Bool(model_statistics.incomplete as jboolean),
+        JValue::Object(&cache_error),
+    ])?;
+    Ok(kt_model_obj)
+}
+
+pub(crate) fn convert_snapshot_to_object<'local> (env: &mut JNIEnv<'local>, snapshot : ModelSnapshot) -> CoreResult<JObject<'local>> {
+    let array = convert_lang_stats_to_array(env, &snapshot.languages)?;
+    let kt_snapshot = env.new_object("me/sudodios/codewalker/models/ModelSnapshot", "(JJJJJJJJJJ[Lme/sudodios/codewalker/models/ModelLangStats;)V", &[
+        JValue::Long(snapshot.id as jlong),
+        JValue::Long(snapshot.projectId as jlong),
+        JValue::Long(snapshot.createdAt as jlong),
+        JValue::Long(snapshot.totalFilesCount as jlong),
+        JValue::Long(snapshot.totalCodeLinesCount as jlong),
+        JValue::Long(snapshot.totalCommentLinesCount as jlong),
+        JValue::Long(snapshot.totalDocLinesCount as jlong),
+        JValue::Long(snapshot.totalBlankLinesCount as jlong),
+        JValue::Long(snapshot.totalFileTypesCount as jlong),
+        JValue::Long(snapshot.sizeOnDisk as jlong),
+        JValue::Object(&*array),
+    ])?;
+    Ok(kt_snapshot)
+}
+
+pub(crate) fn convert_snapshots_to_array<'local> (env: &mut JNIEnv<'local>, snapshots : Vec<ModelSnapshot>) -> CoreResult<JObjectArray<'local>> {
+    let kt_snapshot_class = env.find_class("me/sudodios/codewalker/models/ModelSnapshot")?;
+    let array = env.new_object_array(snapshots.len() as i32, &kt_snapshot_class, JObject::null())?;
+    for (i, snapshot) in snapshots.into_iter().enumerate() {
+        let kt_snapshot = convert_snapshot_to_object(env, snapshot)?;
+        env.set_object_array_element(&array, i as i32, &kt_snapshot)?;
+        env.delete_local_ref(kt_snapshot)?;
+    }
+    Ok(array)
+}
+
+pub(crate) fn convert_snapshot_diff_to_object<'local> (env: &mut JNIEnv<'local>, diff : ModelSnapshotDiff) -> CoreResult<JObject<'local>> {
+    let kt_lang_delta_class = env.find_class("me/sudodios/codewalker/models/ModelLangDelta")?;
+    let array = env.new_object_array(diff.languages.len() as i32, &kt_lang_delta_class, JObject::null())?;
+    for (i, lang) in diff.languages.iter().enumerate() {
+        let kt_lang_delta = env.new_object(&kt_lang_delta_class, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JJJJJJ)V", &[
+            JValue::Object(&*env.new_string(lang.name.clone())?),
+            JValue::Object(&*env.new_string(lang.color.clone())?),
+            JValue::Object(&*env.new_string(lang.change.clone())?),
+            JValue::Long(lang.filesDelta as jlong),
+            JValue::Long(lang.linesDelta as jlong),
+            JValue::Long(lang.codeLinesDelta as jlong),
+            JValue::Long(lang.commentLinesDelta as jlong),
+            JValue::Long(lang.docLinesDelta as jlong),
+            JValue::Long(lang.blankLinesDelta as jlong),
+        ])?;
+        env.set_object_array_element(&array, i as i32, &kt_lang_delta)?;
+        env.delete_local_ref(kt_lang_delta)?;
+    }
+    let kt_snapshot_diff = env.new_object("me/sudodios/codewalker/models/ModelSnapshotDiff", "(JJJJJJJJ[Lme/sudodios/codewalker/models/ModelLangDelta;)V", &[
+        JValue::Long(diff.fromSnapshotId as jlong),
+        JValue::Long(diff.toSnapshotId as jlong),
+        JValue::Long(diff.filesDelta as jlong),
+        JValue::Long(diff.codeLinesDelta as jlong),
+        JValue::Long(diff.commentLinesDelta as jlong),
+        JValue::Long(diff.docLinesDelta as jlong),
+        JValue::Long(diff.blankLinesDelta as jlong),
+        JValue::Long(diff.sizeOnDiskDelta as jlong),
+        JValue::Object(&*array),
+    ])?;
+    Ok(kt_snapshot_diff)
+}
+
+pub(crate) fn convert_file_page_to_object<'local> (env: &mut JNIEnv<'local>, page : ModelFilePage) -> CoreResult<JObject<'local>> {
+    let kt_file_stats_class = env.find_class("me/sudodios/codewalker/models/ModelFileStats")?;
+    let array = env.new_object_array(page.files.len() as i32, &kt_file_stats_class, JObject::null())?;
+    for (i, file) in page.files.iter().enumerate() {
+        let path = env.new_string(&file.path)?;
+        let language = env.new_string(&file.language)?;
+        let kt_file_stats = env.new_object(&kt_file_stats_class, "(Ljava/lang/String;Ljava/lang/String;JJJJJJ)V", &[
+            JValue::Object(&path),
+            JValue::Object(&language),
+            JValue::Long(file.totalLinesCount as jlong),
+            JValue::Long(file.codeLinesCount as jlong),
+            JValue::Long(file.commentLinesCount as jlong),
+            JValue::Long(file.docLinesCount as jlong),
+            JValue::Long(file.blankLinesCount as jlong),
+            JValue::Long(file.complexity as jlong),
+        ])?;
+        env.set_object_array_element(&array, i as i32, &kt_file_stats)?;
+        env.delete_local_ref(kt_file_stats)?;
+        env.delete_local_ref(path)?;
+        env.delete_local_ref(language)?;
+    }
+    let kt_file_page = env.new_object("me/sudodios/codewalker/models/ModelFilePage", "(JJ[Lme/sudodios/codewalker/models/ModelFileStats;)V", &[
+        JValue::Long(page.totalCount as jlong),
+        JValue::Long(page.offset as jlong),
+        JValue::Object(&*array),
+    ])?;
+    Ok(kt_file_page)
+}
+
+pub(crate) fn convert_dir_stats_to_array<'local> (env: &mut JNIEnv<'local>, dirs : Vec<ModelDirStats>) -> CoreResult<JObjectArray<'local>> {
+    let kt_dir_stats_class = env.find_class("me/sudodios/codewalker/models/ModelDirStats")?;
+    let array = env.new_object_array(dirs.len() as i32, &kt_dir_stats_class, JObject::null())?;
+    for (i, dir) in dirs.iter().enumerate() {
+        let path = env.new_string(&dir.path)?;
+        let languages = convert_lang_stats_to_array(env, &dir.languages)?;
+        let kt_dir_stats = env.new_object(&kt_dir_stats_class, "(Ljava/lang/String;IJJJJJJ[Lme/sudodios/codewalker/models/ModelLangStats;)V", &[
+            JValue::Object(&path),
+            JValue::Int(dir.depth as jint),
+            JValue::Long(dir.filesCount as jlong),
+            JValue::Long(dir.totalLinesCount as jlong),
+            JValue::Long(dir.codeLinesCount as jlong),
+            JValue::Long(dir.commentLinesCount as jlong),
+            JValue::Long(dir.docLinesCount as jlong),
+            JValue::Long(dir.blankLinesCount as jlong),
+            JValue::Object(&languages),
+        ])?;
+        env.set_object_array_element(&array, i as i32, &kt_dir_stats)?;
+        env.delete_local_ref(kt_dir_stats)?;
+        env.delete_local_ref(languages)?;
+        env.delete_local_ref(path)?;
+    }
+    Ok(array)
+}
+
+fn convert_author_stats_to_array<'local> (env: &mut JNIEnv<'local>, authors : &[ModelAuthorStats]) -> CoreResult<JObjectArray<'local>> {
+    let kt_author_stats_class = env.find_class("me/sudodios/codewalker/models/ModelAuthorStats")?;
+    let array = env.new_object_array(authors.len() as i32, &kt_author_stats_class, JObject::null())?;
+    for (i, author) in authors.iter().enumerate() {
+        let name = env.new_string(&author.name)?;
+        let email = env.new_string(&author.email)?;
+        let kt_author_stats = env.new_object(&kt_author_stats_class, "(Ljava/lang/String;Ljava/lang/String;JJJJJJ)V", &[
+            JValue::Object(&name),
+            JValue::Object(&email),
+            JValue::Long(author.filesCount as jlong),
+            JValue::Long(author.totalLinesCount as jlong),
+            JValue::Long(author.codeLinesCount as jlong),
+            JValue::Long(author.commentLinesCount as jlong),
+            JValue::Long(author.docLinesCount as jlong),
+            JValue::Long(author.blankLinesCount as jlong),
+        ])?;
+        env.set_object_array_element(&array, i as i32, &kt_author_stats)?;
+        env.delete_local_ref(kt_author_stats)?;
+        env.delete_local_ref(name)?;
+        env.delete_local_ref(email)?;
+    }
+    Ok(array)
+}
+
+pub(crate) fn convert_blame_to_object<'local> (env: &mut JNIEnv<'local>, blame : ModelBlame) -> CoreResult<JObject<'local>> {
+    let kt_lang_authors_class = env.find_class("me/sudodios/codewalker/models/ModelLangAuthors")?;
+    let languages = env.new_object_array(blame.languages.len() as i32, &kt_lang_authors_class, JObject::null())?;
+    for (i, lang) in blame.languages.iter().enumerate() {
+        let name = env.new_string(&lang.name)?;
+        let color = env.new_string(&lang.color)?;
+        let authors = convert_author_stats_to_array(env, &lang.authors)?;
+        let kt_lang_authors = env.new_object(&kt_lang_authors_class, "(Ljava/lang/String;Ljava/lang/String;[Lme/sudodios/codewalker/models/ModelAuthorStats;)V", &[
+            JValue::Object(&name),
+            JValue::Object(&color),
+            JValue::Object(&authors),
+        ])?;
+        env.set_object_array_element(&languages, i as i32, &kt_lang_authors)?;
+        env.delete_local_ref(kt_lang_authors)?;
+        env.delete_local_ref(authors)?;
+        env.delete_local_ref(name)?;
+        env.delete_local_ref(color)?;
+    }
+    let authors = convert_author_stats_to_array(env, &blame.authors)?;
+    let kt_blame = env.new_object("me/sudodios/codewalker/models/ModelBlame", "([Lme/sudodios/codewalker/models/ModelLangAuthors;[Lme/sudodios/codewalker/models/ModelAuthorStats;J)V", &[
+        JValue::Object(&*languages),
+        JValue::Object(&*authors),
+        JValue::Long(blame.skippedFilesCount as jlong),
+    ])?;
+    Ok(kt_blame)
+}
+
+pub(crate) fn convert_scan_progress_to_object<'local> (env: &mut JNIEnv<'local>, progress : ModelScanProgress) -> CoreResult<JObject<'local>> {
+    let kt_scan_progress_class = env.find_class("me/sudodios/codewalker/models/ModelScanProgress")?;
+    let current_path = env.new_string(progress.currentPath)?;
+    let kt_scan_progress = env.new_object(&kt_scan_progress_class, "(JJLjava/lang/String;JJJZZ)V", &[
+        JValue::Long(progress.discoveredFilesCount as jlong),
+        JValue::Long(progress.parsedFilesCount as jlong),
+        JValue::Object(&current_path),
+        JValue::Long(progress.codeLinesCount as jlong),
+        JValue::Long(progress.commentLinesCount as jlong),
+        JValue::Long(progress.blankLinesCount as jlong),
+        JValue::Bool(progress.finished as jboolean),
+        JValue::Bool(progress.cancelled as jboolean),
+    ])?;
+    env.delete_local_ref(current_path)?;
+    env.delete_local_ref(kt_scan_progress_class)?;
+    Ok(kt_scan_progress)
+}
+
+pub(crate) fn to_project_id (p_id : jlong) -> CoreResult<usize> {
+    if p_id < 0 {
+        return Err(CoreError::InvalidArgument(format!("{} is not a valid project id", p_id)));
+    }
+    Ok(p_id as usize)
 }
\ No newline at end of file
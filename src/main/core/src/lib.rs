@@ -1,136 +1,347 @@
+use std::cmp::Reverse;
+use std::ptr::null_mut;
+use std::sync::Arc;
+
 use jni::JNIEnv;
 use jni::objects::{JClass, JObject, JObjectArray, JString};
-use jni::sys::{jboolean, jlong, jobject, jobjectArray, jstring};
-use tokei::{Config, Languages};
+use jni::sys::{jboolean, jint, jlong, jobject, jobjectArray, jstring, JNI_FALSE};
+
+use tokei::{CancellationToken, LanguageType, Languages, ReportCache, Sort};
 
-use crate::colors::ColorFinder;
-use crate::jni_utils::{convert_data_class_to_model_db, convert_java_array_to_vec, convert_model_stat_to_object};
-use crate::models::{ModelLangStats, ModelStatistics};
+use crate::error::{jni_call, CoreError, CoreResult};
+use crate::jni_utils::{convert_blame_to_object, convert_data_class_to_model_db, convert_dir_stats_to_array, convert_file_page_to_object, convert_java_array_to_vec, convert_model_stat_to_object, convert_scan_progress_to_object, convert_snapshot_diff_to_object, convert_snapshot_to_object, convert_snapshots_to_array, to_project_id};
+use crate::models::{ModelCategoryStats, ModelLangStats, ModelStatistics};
+use crate::progress::ScanProgress;
+use crate::project_config::ProjectConfig;
 use crate::util::{get_current_time_millis, get_sum_of_dirs_sizes};
 
 mod models;
 mod colors;
 mod util;
 mod database;
+mod migrations;
 mod jni_utils;
+mod error;
+mod progress;
+mod project_config;
+mod reports;
+mod scan;
+mod trends;
 
 #[allow(non_snake_case)]
 #[no_mangle]
-pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_version<'local>(env: JNIEnv<'local>, _class: JClass<'local>) -> jstring {
-    env.new_string("1.0.0-a").unwrap().into_raw()
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_version<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>) -> jstring {
+    jni_call(&mut env, null_mut(), |env| {
+        Ok(env.new_string("1.0.0-a")?.into_raw())
+    })
 }
 
 /*db*/
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_initDB<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,dbPath : JString<'local>) {
-    let db_path : String = env.get_string(&dbPath).expect("").into();
-    database::init_db(db_path.as_str());
+    jni_call(&mut env, (), |env| {
+        let db_path : String = env.get_string(&dbPath)?.into();
+        database::init_db(db_path.as_str())
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_closeDB<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>) {
+    jni_call(&mut env, (), |_| {
+        database::close_db();
+        Ok(())
+    })
 }
 
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_createProject<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,modelP : JObject<'local>) -> jlong {
-    let dbModel = convert_data_class_to_model_db(&mut env, &modelP);
-    let insertedId = database::create_project(&dbModel);
-    return insertedId as jlong
+    jni_call(&mut env, -1, |env| {
+        let dbModel = convert_data_class_to_model_db(env, &modelP)?;
+        let insertedId = database::create_project(&dbModel)?;
+        Ok(insertedId as jlong)
+    })
 }
 
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_updateProject<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,modelP : JObject<'local>) {
-    let dbModel = convert_data_class_to_model_db(&mut env,&modelP);
-    database::update_project(dbModel)
+    jni_call(&mut env, (), |env| {
+        let dbModel = convert_data_class_to_model_db(env,&modelP)?;
+        database::update_project(dbModel)
+    })
 }
 
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_refreshAnalyze<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,
                                                                                         pId : jlong,updateTime : jlong,languages : JString<'local>,totals : JString<'local>) {
-    let _languages: String = env.get_string(&languages).expect("").into();
-    let _totals: String = env.get_string(&totals).expect("").into();
-    database::refresh_analyze(pId as usize,updateTime as usize,_languages.as_str(),_totals.as_str())
+    jni_call(&mut env, (), |env| {
+        let _languages: String = env.get_string(&languages)?.into();
+        let _totals: String = env.get_string(&totals)?.into();
+        database::refresh_analyze(to_project_id(pId)?,updateTime as usize,_languages.as_str(),_totals.as_str())
+    })
 }
 
 #[allow(non_snake_case)]
 #[no_mangle]
-pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_removeProject<'local>(_: JNIEnv<'local>, _class: JClass<'local>, pId : jlong) {
-    database::remove_project(pId as usize)
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_removeProject<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, pId : jlong) {
+    jni_call(&mut env, (), |_| {
+        database::remove_project(to_project_id(pId)?)
+    })
 }
 
 #[allow(non_snake_case)]
 #[no_mangle]
-pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_readProjects<'local>(env: JNIEnv<'local>, _class: JClass<'local>) -> jobjectArray {
-    let projectsList = database::read_projects();
-    return jni_utils::convert_vec_to_j_o_array(env,projectsList).into_raw()
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_readProjects<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>) -> jobjectArray {
+    jni_call(&mut env, null_mut(), |env| {
+        let projectsList = database::read_projects()?;
+        Ok(jni_utils::convert_vec_to_j_o_array(env,projectsList)?.into_raw())
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_readSnapshots<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, pId : jlong) -> jobjectArray {
+    jni_call(&mut env, null_mut(), |env| {
+        let snapshots = database::read_snapshots(to_project_id(pId)?)?;
+        Ok(convert_snapshots_to_array(env,snapshots)?.into_raw())
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_readSnapshot<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, snapshotId : jlong) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        if snapshotId < 0 {
+            return Err(CoreError::InvalidArgument(format!("{} is not a valid snapshot id", snapshotId)));
+        }
+        let snapshot = database::read_snapshot(snapshotId as usize)?;
+        Ok(convert_snapshot_to_object(env,snapshot)?.into_raw())
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_compareSnapshots<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, fromId : jlong, toId : jlong) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        if fromId < 0 || toId < 0 {
+            return Err(CoreError::InvalidArgument(format!("{} and {} are not valid snapshot ids", fromId, toId)));
+        }
+        let from = database::read_snapshot(fromId as usize)?;
+        let to = database::read_snapshot(toId as usize)?;
+        if from.projectId != to.projectId {
+            return Err(CoreError::InvalidArgument(format!("snapshots {} and {} belong to different projects", fromId, toId)));
+        }
+        Ok(convert_snapshot_diff_to_object(env,trends::compare_snapshots(&from,&to))?.into_raw())
+    })
 }
 /*end db*/
 
 
+/*scan*/
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_startScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,
+                                                                                  folders : JObjectArray<'local>,ignored : JObjectArray<'local>,
+                                                                                  hidden : jboolean,noIgnore : jboolean,docAsComm : jboolean) -> jlong {
+    jni_call(&mut env, -1, |env| {
+        let _folders = convert_java_array_to_vec(env, &folders)?;
+        let _ignored = convert_java_array_to_vec(env, &ignored)?;
+        check_folders(&_folders)?;
+        let projectConfig = ProjectConfig {
+            hidden: hidden != 0,
+            no_ignore: noIgnore != 0,
+            doc_as_comment: docAsComm != 0,
+            ..ProjectConfig::default()
+        };
+        Ok(scan::start_scan(_folders,_ignored,projectConfig,None) as jlong)
+    })
+}
+
+/// Rescans a saved project with its own folders and settings, returns a scan handle.
+/// Files unchanged since the previous rescan reuse their stored results.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_analyzeProject<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, pId : jlong) -> jlong {
+    jni_call(&mut env, -1, |_| {
+        let p_id = to_project_id(pId)?;
+        let project = database::read_project(p_id)?;
+        let _folders : Vec<String> = serde_json::from_str(&project.root_folders)?;
+        let _ignored : Vec<String> = serde_json::from_str(&project.ignored_folders)?;
+        check_folders(&_folders)?;
+        let cache = database::read_file_cache(p_id,&project.configs.tokei_config(),project.configs.content_hash)?;
+        Ok(scan::start_scan(_folders,_ignored,project.configs,Some((p_id,Arc::new(cache)))) as jlong)
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_cancelScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong) -> jboolean {
+    jni_call(&mut env, JNI_FALSE, |_| {
+        Ok(scan::cancel_scan(handle) as jboolean)
+    })
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_pollScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        match scan::poll_scan(handle) {
+            Some(progress) => Ok(convert_scan_progress_to_object(env,progress)?.into_raw()),
+            None => Ok(JObject::null().into_raw())
+        }
+    })
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
-pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_getDirCodeStats<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>,
-                                                                                        folders : JObjectArray<'local>,ignored : JObjectArray<'local>,
-                                                                                        hidden : jboolean,noIgnore : jboolean,docAsComm : jboolean) -> jobject {
-    //parse
-    let _folders = convert_java_array_to_vec(&mut env, &folders);
-    let _ignored = convert_java_array_to_vec(&mut env, &ignored);
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_finishScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        let statModel = scan::finish_scan(handle)?;
+        Ok(convert_model_stat_to_object(env,statModel)?.into_raw())
+    })
+}
 
-    let _hidden: bool = if hidden == 0 { false } else { true };
-    let _noIgnore: bool = if noIgnore == 0 { false } else { true };
-    let _docAsComm: bool = if docAsComm == 0 { false } else { true };
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_releaseScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong) -> jboolean {
+    jni_call(&mut env, JNI_FALSE, |_| {
+        Ok(scan::release_scan(handle) as jboolean)
+    })
+}
+
+/// Lists the files of a finished scan, `language` being null for every
+/// language and `sort` one of tokei's sort names.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_listFiles<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong,
+                                                                                  language : JString<'local>,sort : JString<'local>,offset : jint,limit : jint) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        let _language = if language.is_null() {
+            None
+        } else {
+            let name : String = env.get_string(&language)?.into();
+            Some(name.parse::<LanguageType>().map_err(|e| CoreError::InvalidArgument(e.to_string()))?)
+        };
+        let _sort : String = env.get_string(&sort)?.into();
+        let _sort = _sort.parse::<Sort>().map_err(CoreError::InvalidArgument)?;
+        if offset < 0 || limit < 0 {
+            return Err(CoreError::InvalidArgument(format!("invalid page offset {} and limit {}", offset, limit)));
+        }
+        let results = scan::scan_results(handle)?;
+        let page = reports::list_files(&results.languages,_language,_sort,offset as usize,limit as usize);
+        Ok(convert_file_page_to_object(env,page)?.into_raw())
+    })
+}
 
-    //conf & start
-    let languages = init_lib(_folders.as_slice(),_ignored.iter().map(|s| s.as_str()).collect::<Vec<_>>().as_slice(),_hidden,_noIgnore,_docAsComm);
-    let colorFinder = ColorFinder::init();
+/// The directory tree of a finished scan, flattened parents first, `depth`
+/// of 0 listing every level.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_directoryTree<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong, depth : jint) -> jobjectArray {
+    jni_call(&mut env, null_mut(), |env| {
+        if depth < 0 {
+            return Err(CoreError::InvalidArgument(format!("invalid depth {}", depth)));
+        }
+        let results = scan::scan_results(handle)?;
+        let maxDepth = if depth == 0 { None } else { Some(depth as usize) };
+        let dirs = reports::list_directories(&results.languages,&results.folders,&results.config.tokei_config(),maxDepth);
+        Ok(convert_dir_stats_to_array(env,dirs)?.into_raw())
+    })
+}
+/// Splits the lines of a finished scan by the author who changed them last,
+/// per language and in total, reading the local git repositories the files
+/// are in.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_me_sudodios_codewalker_core_LibCore_blameScan<'local>(mut env: JNIEnv<'local>, _class: JClass<'local>, handle : jlong) -> jobject {
+    jni_call(&mut env, null_mut(), |env| {
+        let results = scan::scan_results(handle)?;
+        let config = results.config.tokei_config();
+        let blame = results.languages.blame(&config);
+        Ok(convert_blame_to_object(env,reports::blame_authors(&blame,&config))?.into_raw())
+    })
+}
+/*end scan*/
+
+fn check_folders(folders : &[String]) -> CoreResult<()> {
+    if folders.is_empty() {
+        return Err(CoreError::InvalidArgument("at least one folder is required to scan".to_string()));
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+fn collect_statistics(languages : &Languages,folders : &Vec<String>,project_config : &ProjectConfig,incomplete : bool) -> CoreResult<ModelStatistics> {
+    let config = project_config.tokei_config();
     let mut langResults: Vec<ModelLangStats> = Vec::new();
     for lang in languages.iter() {
         let res = ModelLangStats {
-            name: lang.0.name().parse().unwrap(),
-            color: colorFinder.get_color_by_lang_name(lang.0.name()),
+            name: lang.0.name().to_string(),
+            color: config.color(*lang.0).to_string(),
             filesCount: lang.1.reports.len(),
             totalLinesCount: lang.1.lines(),
             codeLinesCount: lang.1.code,
             commentLinesCount: lang.1.comments,
-            blankLinesCount: lang.1.blanks
+            docLinesCount: lang.1.docs,
+            blankLinesCount: lang.1.blanks,
+            complexity: lang.1.complexity
         };
         langResults.push(res)
     }
+    match project_config.sort {
+        Some(Sort::Blanks) => langResults.sort_by_key(|l| Reverse(l.blankLinesCount)),
+        Some(Sort::Comments) => langResults.sort_by_key(|l| Reverse(l.commentLinesCount)),
+        Some(Sort::Docs) => langResults.sort_by_key(|l| Reverse(l.docLinesCount)),
+        Some(Sort::Code) => langResults.sort_by_key(|l| Reverse(l.codeLinesCount)),
+        Some(Sort::Complexity) => langResults.sort_by_key(|l| Reverse(l.complexity)),
+        Some(Sort::Files) => langResults.sort_by_key(|l| Reverse(l.filesCount)),
+        Some(Sort::Lines) => langResults.sort_by_key(|l| Reverse(l.totalLinesCount)),
+        None => {}
+    }
 
     let totalFilesCount : usize = langResults.iter().map(|s| s.filesCount).sum();
     let totalCodeLinesCount : usize = langResults.iter().map(|s| s.codeLinesCount).sum();
     let totalCommentLinesCount : usize = langResults.iter().map(|s| s.commentLinesCount).sum();
+    let totalDocLinesCount : usize = langResults.iter().map(|s| s.docLinesCount).sum();
     let totalBlankLinesCount : usize = langResults.iter().map(|s| s.blankLinesCount).sum();
     let totalFileTypesCount : usize = langResults.len();
+    let categories = languages.total_by_category().into_iter().map(|(category, total)| ModelCategoryStats {
+        name: category.name().to_string(),
+        filesCount: total.children.values().map(Vec::len).sum(),
+        totalLinesCount: total.lines(),
+        codeLinesCount: total.code,
+        commentLinesCount: total.comments,
+        docLinesCount: total.docs,
+        blankLinesCount: total.blanks,
+    }).collect();
 
-    let statModel = ModelStatistics {
+    Ok(ModelStatistics {
         totalFilesCount,
         totalCodeLinesCount,
         totalCommentLinesCount,
+        totalDocLinesCount,
         totalBlankLinesCount,
         totalFileTypesCount,
-        sizeOnDisk: get_sum_of_dirs_sizes(&_folders),
+        sizeOnDisk: get_sum_of_dirs_sizes(folders)?,
         languages: langResults,
-        lastUpdateTime: get_current_time_millis()
-    };
-    let convert = convert_model_stat_to_object(env,statModel);
-    convert.into_raw()
-}
-
-fn init_lib(paths : &[String],ignore : &[&str],hidden : bool,no_ignore : bool,doc_as_comment : bool) -> Languages {
-    let config = Config {
-        columns: None,
-        hidden : Option::from(hidden),
-        no_ignore: Option::from(no_ignore),
-        no_ignore_parent: None,
-        no_ignore_dot: None,
-        no_ignore_vcs: None,
-        treat_doc_strings_as_comments: Option::from(doc_as_comment),
-        sort: None,
-        types: None,
-        for_each_fn: None,
-    };
+        categories,
+        lastUpdateTime: get_current_time_millis(),
+        incomplete,
+        cacheError: None
+    })
+}
+
+fn init_lib(paths : &[String],ignored : &[String],project_config : &ProjectConfig,cache : Option<Arc<ReportCache>>,progress : &Arc<ScanProgress>,cancellation : &CancellationToken) -> Languages {
+    let mut config = project_config.tokei_config();
+    config.cancellation = Some(cancellation.clone());
+    config.cache = cache;
+    progress.attach(&mut config);
+    let ignore = ignored.iter().chain(project_config.excluded.iter()).map(|s| s.as_str()).collect::<Vec<_>>();
     let mut languages = Languages::new();
-    languages.get_statistics(paths, ignore, &config);
+    languages.get_statistics(paths, ignore.as_slice(), &config);
     languages
 }
\ No newline at end of file
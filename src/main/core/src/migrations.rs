@@ -0,0 +1,201 @@
+use rusqlite::Connection;
+use crate::error::{CoreError, CoreResult};
+
+/// Schema changes in the order they were introduced. Migration `n` (1-based)
+/// upgrades a database from `user_version` `n - 1` to `n`, so entries must
+/// never be edited or reordered once released, only appended.
+const MIGRATIONS: &[&str] = &[
+    // 1: the original schema, databases created before versioning already have it
+    "CREATE TABLE IF NOT EXISTS projects (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT,
+        root_folders TEXT,
+        ignored_folders TEXT,
+        last_update INTEGER,
+        configs TEXT,
+        analyze TEXT,
+        totals TEXT)",
+    // 2: history of every analysis run, seeded with the last analysis stored on each project
+    "CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_id INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        files INTEGER NOT NULL,
+        code INTEGER NOT NULL,
+        comments INTEGER NOT NULL,
+        blanks INTEGER NOT NULL,
+        file_types INTEGER NOT NULL,
+        size_on_disk INTEGER NOT NULL);
+    CREATE INDEX snapshots_by_project ON snapshots (project_id, created_at);
+    CREATE TABLE snapshot_languages (
+        snapshot_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        files INTEGER NOT NULL,
+        lines INTEGER NOT NULL,
+        code INTEGER NOT NULL,
+        comments INTEGER NOT NULL,
+        blanks INTEGER NOT NULL,
+        PRIMARY KEY (snapshot_id, name));
+    INSERT INTO snapshots (project_id,created_at,files,code,comments,blanks,file_types,size_on_disk)
+        SELECT id,IFNULL(last_update,0),
+               IFNULL(json_extract(totals,'$.totalFilesCount'),0),
+               IFNULL(json_extract(totals,'$.totalCodeLinesCount'),0),
+               IFNULL(json_extract(totals,'$.totalCommentLinesCount'),0),
+               IFNULL(json_extract(totals,'$.totalBlankLinesCount'),0),
+               IFNULL(json_extract(totals,'$.totalFileTypesCount'),0),
+               IFNULL(json_extract(totals,'$.sizeOnDisk'),0)
+        FROM projects WHERE json_valid(totals);
+    INSERT OR IGNORE INTO snapshot_languages (snapshot_id,name,files,lines,code,comments,blanks)
+        SELECT snapshots.id,json_extract(lang.value,'$.name'),
+               IFNULL(json_extract(lang.value,'$.filesCount'),0),
+               IFNULL(json_extract(lang.value,'$.totalLinesCount'),0),
+               IFNULL(json_extract(lang.value,'$.codeLinesCount'),0),
+               IFNULL(json_extract(lang.value,'$.commentLinesCount'),0),
+               IFNULL(json_extract(lang.value,'$.blankLinesCount'),0)
+        FROM snapshots JOIN projects ON projects.id = snapshots.project_id, json_each(projects.analyze) AS lang
+        WHERE json_valid(projects.analyze) AND json_extract(lang.value,'$.name') IS NOT NULL;",
+    // 3: per-file results of the last scan of each project, reused by the next one
+    "CREATE TABLE file_cache (
+        project_id INTEGER NOT NULL,
+        path TEXT NOT NULL,
+        language TEXT NOT NULL,
+        modified INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        hash INTEGER,
+        stats TEXT NOT NULL,
+        PRIMARY KEY (project_id, path));",
+    // 4: branch counts per language, cached files are counted again as their stats lack them
+    "ALTER TABLE snapshot_languages ADD COLUMN complexity INTEGER NOT NULL DEFAULT 0;
+    DELETE FROM file_cache;",
+    // 5: documentation lines per language, split out of comments so cached files are counted again
+    "ALTER TABLE snapshot_languages ADD COLUMN docs INTEGER NOT NULL DEFAULT 0;
+    DELETE FROM file_cache;",
+    // 6: generated or vendored class of cached files, which are parsed again to classify them
+    "ALTER TABLE file_cache ADD COLUMN class TEXT NOT NULL DEFAULT 'null';
+    DELETE FROM file_cache;",
+    // 7: documentation lines of the whole project, summed from the languages of earlier snapshots
+    "ALTER TABLE snapshots ADD COLUMN docs INTEGER NOT NULL DEFAULT 0;
+    UPDATE snapshots SET docs = (SELECT IFNULL(SUM(docs),0) FROM snapshot_languages WHERE snapshot_id = snapshots.id);",
+];
+
+/// Brings the database up to the latest schema version.
+pub(crate) fn migrate (conn : &mut Connection) -> CoreResult<()> {
+    apply(conn,MIGRATIONS)
+}
+
+pub(crate) fn schema_version (conn : &Connection) -> CoreResult<usize> {
+    Ok(conn.pragma_query_value(None,"user_version",|row| row.get::<_, i64>(0))? as usize)
+}
+
+fn apply (conn : &mut Connection,migrations : &[&str]) -> CoreResult<()> {
+    let current = schema_version(conn)?;
+    if current > migrations.len() {
+        return Err(CoreError::InvalidArgument(format!(
+            "database schema version {} is newer than the supported version {}",current,migrations.len())));
+    }
+    for (index, migration) in migrations.iter().enumerate().skip(current) {
+        // each step commits together with its version bump, a failing step leaves the previous version intact
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None,"user_version",(index + 1) as i64)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database as written by releases before migrations existed.
+    fn unversioned_fixture () -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE IF NOT EXISTS projects (
+                          id INTEGER PRIMARY KEY AUTOINCREMENT,
+                          name TEXT,
+                          root_folders TEXT,
+                          ignored_folders TEXT,
+                          last_update INTEGER,
+                          configs TEXT,
+                          analyze TEXT,
+                          totals TEXT);
+                          INSERT INTO projects (name,root_folders,ignored_folders,last_update,configs,analyze,totals)
+                          VALUES ('walker','[\"/src\"]','[]',1700000000000,'{}',
+                          '[{\"name\":\"Rust\",\"color\":\"#DEA584\",\"filesCount\":3,\"totalLinesCount\":120,\"codeLinesCount\":100,\"commentLinesCount\":12,\"blankLinesCount\":8}]',
+                          '{\"totalFilesCount\":3,\"totalCodeLinesCount\":100,\"totalCommentLinesCount\":12,\"totalBlankLinesCount\":8,\"totalFileTypesCount\":1,\"sizeOnDisk\":4096}');").unwrap();
+        conn
+    }
+
+    #[test]
+    fn upgrades_unversioned_database_and_keeps_rows () {
+        let mut conn = unversioned_fixture();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+        let name : String = conn.query_row("SELECT name FROM projects WHERE id = 1",[],|row| row.get(0)).unwrap();
+        assert_eq!(name, "walker");
+    }
+
+    #[test]
+    fn seeds_snapshots_from_stored_analysis () {
+        let mut conn = unversioned_fixture();
+        migrate(&mut conn).unwrap();
+
+        let (project_id, created_at, code, size) : (i64, i64, i64, i64) = conn.query_row(
+            "SELECT project_id,created_at,code,size_on_disk FROM snapshots",[],|row| Ok((row.get(0)?,row.get(1)?,row.get(2)?,row.get(3)?))).unwrap();
+        assert_eq!((project_id, created_at, code, size), (1, 1700000000000, 100, 4096));
+        let (name, files, comments) : (String, i64, i64) = conn.query_row(
+            "SELECT name,files,comments FROM snapshot_languages",[],|row| Ok((row.get(0)?,row.get(1)?,row.get(2)?))).unwrap();
+        assert_eq!((name.as_str(), files, comments), ("Rust", 3, 12));
+    }
+
+    #[test]
+    fn sums_documentation_of_earlier_snapshots () {
+        let mut conn = unversioned_fixture();
+        apply(&mut conn,&MIGRATIONS[..6]).unwrap();
+        conn.execute("UPDATE snapshot_languages SET docs = 5",[]).unwrap();
+        conn.execute("INSERT INTO snapshot_languages (snapshot_id,name,files,lines,code,comments,docs,blanks) VALUES (1,'TOML',1,4,4,0,2,0)",[]).unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let docs : i64 = conn.query_row("SELECT docs FROM snapshots WHERE id = 1",[],|row| row.get(0)).unwrap();
+        assert_eq!(docs, 7);
+    }
+
+    #[test]
+    fn creates_fresh_database () {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+        let count : i64 = conn.query_row("SELECT COUNT(*) FROM projects",[],|row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op () {
+        let mut conn = unversioned_fixture();
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn failing_migration_rolls_back () {
+        let mut conn = unversioned_fixture();
+        let migrations = [MIGRATIONS[0], "ALTER TABLE projects ADD COLUMN pinned INTEGER; SELECT * FROM missing_table;"];
+
+        assert!(apply(&mut conn,&migrations).is_err());
+
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        assert!(conn.prepare("SELECT pinned FROM projects").is_err());
+    }
+
+    #[test]
+    fn rejects_newer_database () {
+        let mut conn = unversioned_fixture();
+        conn.pragma_update(None,"user_version",(MIGRATIONS.len() + 1) as i64).unwrap();
+        assert!(matches!(migrate(&mut conn), Err(CoreError::InvalidArgument(_))));
+    }
+}
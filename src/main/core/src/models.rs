@@ -1,3 +1,5 @@
+use crate::project_config::ProjectConfig;
+
 #[allow(non_snake_case)]
 pub struct ModelLangStats {
     pub name : String,
@@ -6,7 +8,9 @@ pub struct ModelLangStats {
     pub totalLinesCount : usize,
     pub codeLinesCount : usize,
     pub commentLinesCount : usize,
+    pub docLinesCount : usize,
     pub blankLinesCount : usize,
+    pub complexity : usize,
 }
 
 #[allow(non_snake_case)]
@@ -17,7 +21,7 @@ pub struct ModelStatisticsDB {
     pub root_folders : String,
     pub ignored_folders : String,
     pub last_update : usize,
-    pub configs : String,
+    pub configs : ProjectConfig,
     pub analyze : String,
     pub totals : String,
 }
@@ -27,9 +31,139 @@ pub struct ModelStatistics {
     pub totalFilesCount : usize,
     pub totalCodeLinesCount : usize,
     pub totalCommentLinesCount : usize,
+    pub totalDocLinesCount : usize,
     pub totalBlankLinesCount : usize,
     pub totalFileTypesCount : usize,
     pub sizeOnDisk : usize,
     pub languages : Vec<ModelLangStats>,
+    pub categories : Vec<ModelCategoryStats>,
     pub lastUpdateTime : u128,
+    pub incomplete : bool,
+    /// Why the per-file results couldn't be stored for the next scan, which
+    /// then parses every file again.
+    pub cacheError : Option<String>,
+}
+
+/// Totals of the languages of one category, programming, markup, data or prose
+#[allow(non_snake_case)]
+#[derive(Default)]
+pub struct ModelCategoryStats {
+    pub name : String,
+    pub filesCount : usize,
+    pub totalLinesCount : usize,
+    pub codeLinesCount : usize,
+    pub commentLinesCount : usize,
+    pub docLinesCount : usize,
+    pub blankLinesCount : usize,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelScanProgress {
+    pub discoveredFilesCount : usize,
+    pub parsedFilesCount : usize,
+    pub currentPath : String,
+    pub codeLinesCount : usize,
+    pub commentLinesCount : usize,
+    pub blankLinesCount : usize,
+    pub finished : bool,
+    pub cancelled : bool,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelSnapshot {
+    pub id : usize,
+    pub projectId : usize,
+    pub createdAt : usize,
+    pub totalFilesCount : usize,
+    pub totalCodeLinesCount : usize,
+    pub totalCommentLinesCount : usize,
+    pub totalDocLinesCount : usize,
+    pub totalBlankLinesCount : usize,
+    pub totalFileTypesCount : usize,
+    pub sizeOnDisk : usize,
+    pub languages : Vec<ModelLangStats>,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelLangDelta {
+    pub name : String,
+    pub color : String,
+    pub change : String,
+    pub filesDelta : isize,
+    pub linesDelta : isize,
+    pub codeLinesDelta : isize,
+    pub commentLinesDelta : isize,
+    pub docLinesDelta : isize,
+    pub blankLinesDelta : isize,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelSnapshotDiff {
+    pub fromSnapshotId : usize,
+    pub toSnapshotId : usize,
+    pub filesDelta : isize,
+    pub codeLinesDelta : isize,
+    pub commentLinesDelta : isize,
+    pub docLinesDelta : isize,
+    pub blankLinesDelta : isize,
+    pub sizeOnDiskDelta : isize,
+    pub languages : Vec<ModelLangDelta>,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelFileStats {
+    pub path : String,
+    pub language : String,
+    pub totalLinesCount : usize,
+    pub codeLinesCount : usize,
+    pub commentLinesCount : usize,
+    pub docLinesCount : usize,
+    pub blankLinesCount : usize,
+    pub complexity : usize,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelFilePage {
+    pub totalCount : usize,
+    pub offset : usize,
+    pub files : Vec<ModelFileStats>,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelAuthorStats {
+    pub name : String,
+    pub email : String,
+    pub filesCount : usize,
+    pub totalLinesCount : usize,
+    pub codeLinesCount : usize,
+    pub commentLinesCount : usize,
+    pub docLinesCount : usize,
+    pub blankLinesCount : usize,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelLangAuthors {
+    pub name : String,
+    pub color : String,
+    pub authors : Vec<ModelAuthorStats>,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelBlame {
+    pub languages : Vec<ModelLangAuthors>,
+    pub authors : Vec<ModelAuthorStats>,
+    pub skippedFilesCount : usize,
+}
+
+#[allow(non_snake_case)]
+pub struct ModelDirStats {
+    pub path : String,
+    pub depth : usize,
+    pub filesCount : usize,
+    pub totalLinesCount : usize,
+    pub codeLinesCount : usize,
+    pub commentLinesCount : usize,
+    pub docLinesCount : usize,
+    pub blankLinesCount : usize,
+    pub languages : Vec<ModelLangStats>,
 }
\ No newline at end of file
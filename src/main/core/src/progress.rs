@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use tokei::{Config, LanguageType, Report};
+
+use crate::models::ModelScanProgress;
+
+/// Live counters of a running scan, updated from tokei's walker and parser threads.
+#[derive(Default)]
+pub struct ScanProgress {
+    discovered : AtomicUsize,
+    parsed : AtomicUsize,
+    code : AtomicUsize,
+    comments : AtomicUsize,
+    blanks : AtomicUsize,
+    current_path : Mutex<String>,
+}
+
+impl ScanProgress {
+    /// Installs the per-file hooks of `config` so they feed this progress.
+    pub fn attach (self : &Arc<Self>,config : &mut Config) {
+        let progress = self.clone();
+        config.for_each_discovered_fn = Some(Arc::new(move |_ : LanguageType,_ : &std::path::Path| {
+            progress.discovered.fetch_add(1,Ordering::Relaxed);
+        }));
+        let progress = self.clone();
+        config.for_each_fn = Some(Arc::new(move |_ : LanguageType,report : Report| {
+            let stats = report.stats.summarise();
+            progress.code.fetch_add(stats.code,Ordering::Relaxed);
+            progress.comments.fetch_add(stats.comments,Ordering::Relaxed);
+            progress.blanks.fetch_add(stats.blanks,Ordering::Relaxed);
+            progress.parsed.fetch_add(1,Ordering::Relaxed);
+            *progress.current_path.lock().unwrap() = report.name.to_string_lossy().into_owned();
+        }));
+    }
+
+    pub fn snapshot (&self) -> ModelScanProgress {
+        ModelScanProgress {
+            discoveredFilesCount: self.discovered.load(Ordering::Relaxed),
+            parsedFilesCount: self.parsed.load(Ordering::Relaxed),
+            currentPath: self.current_path.lock().unwrap().clone(),
+            codeLinesCount: self.code.load(Ordering::Relaxed),
+            commentLinesCount: self.comments.load(Ordering::Relaxed),
+            blankLinesCount: self.blanks.load(Ordering::Relaxed),
+            finished: false,
+            cancelled: false,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use tokei::{Category, Config, LanguageConfig, LanguageType, Sort};
+
+use crate::error::CoreResult;
+
+/// Scan settings saved with a project, stored as JSON in the `configs` column.
+/// Missing keys fall back to their defaults, so configs written by older
+/// versions of the app still load.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub hidden : bool,
+    pub no_ignore : bool,
+    pub no_ignore_parent : bool,
+    pub no_ignore_dot : bool,
+    pub no_ignore_vcs : bool,
+    pub doc_as_comment : bool,
+    /// Only count these languages, every language when `None`.
+    pub types : Option<Vec<LanguageType>>,
+    /// Only count languages of these categories, every category when `None`.
+    pub categories : Option<Vec<Category>>,
+    /// Leave out the languages of these categories.
+    pub excluded_categories : Option<Vec<Category>>,
+    /// Order of the languages in the result, tokei's order when `None`.
+    pub sort : Option<Sort>,
+    /// Extra gitignore-style patterns excluded on top of the ignored folders.
+    pub excluded : Vec<String>,
+    /// Also compare file contents when reusing the results of the previous
+    /// scan, so files touched without being changed aren't parsed again.
+    pub content_hash : bool,
+    /// Colors of languages by display name, as `#RRGGBB`, in place of tokei's.
+    pub colors : BTreeMap<String, String>,
+}
+
+impl ProjectConfig {
+    pub fn from_json (json : &str) -> CoreResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json (&self) -> String {
+        serde_json::to_string(self).expect("ProjectConfig always serializes")
+    }
+
+    /// Whether scanning with `other` counts the same files the same way,
+    /// colors only change how the results are shown.
+    pub fn scans_like (&self,other : &ProjectConfig) -> bool {
+        let without_colors = |config : &ProjectConfig| ProjectConfig { colors: BTreeMap::new(), ..config.clone() };
+        without_colors(self) == without_colors(other)
+    }
+
+    pub fn tokei_config (&self) -> Config {
+        Config {
+            hidden: Some(self.hidden),
+            no_ignore: Some(self.no_ignore),
+            no_ignore_parent: Some(self.no_ignore_parent),
+            no_ignore_dot: Some(self.no_ignore_dot),
+            no_ignore_vcs: Some(self.no_ignore_vcs),
+            treat_doc_strings_as_comments: Some(self.doc_as_comment),
+            sort: self.sort,
+            types: self.types.clone(),
+            categories: self.categories.clone(),
+            excluded_categories: self.excluded_categories.clone(),
+            languages: self.language_configs(),
+            ..Config::default()
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// The colors as tokei language configs, skipping names tokei doesn't know.
+    fn language_configs (&self) -> Option<BTreeMap<LanguageType, LanguageConfig>> {
+        if self.colors.is_empty() {
+            return None;
+        }
+        Some(self.colors.iter().filter_map(|(name, color)| {
+            let language = LanguageType::from_str(name).ok()?;
+            Some((language, LanguageConfig { color: Some(color.clone()), ..LanguageConfig::default() }))
+        }).collect())
+    }
+}
+
+impl ToSql for ProjectConfig {
+    fn to_sql (&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_json()))
+    }
+}
+
+/// Rows without settings or with settings that no longer parse get the
+/// defaults, so one bad row doesn't keep every project from loading.
+impl FromSql for ProjectConfig {
+    /// Rows saved before the column existed are NULL and get the defaults, a config that
+    /// can't be read is an error rather than silently scanning with the defaults.
+    fn column_result (value : ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Null => Ok(ProjectConfig::default()),
+            _ => serde_json::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_configs_saved_by_older_versions () {
+        let config = ProjectConfig::from_json(r#"{"hidden":true,"no_ignore":false,"doc_as_comment":true}"#).unwrap();
+        assert_eq!(config, ProjectConfig { hidden: true, doc_as_comment: true, ..ProjectConfig::default() });
+    }
+
+    #[test]
+    fn round_trips_through_json () {
+        let config = ProjectConfig {
+            no_ignore_vcs: true,
+            types: Some(vec![LanguageType::Rust, LanguageType::Kotlin]),
+            sort: Some(Sort::Code),
+            categories: Some(vec![Category::Programming, Category::Markup]),
+            excluded: vec!["**/generated".to_string()],
+            ..ProjectConfig::default()
+        };
+        let json = config.to_json();
+        assert!(json.contains(r#""sort":"code""#));
+        assert!(json.contains(r#""categories":["programming","markup"]"#));
+        assert_eq!(ProjectConfig::from_json(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn round_trips_through_sqlite () {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let config = ProjectConfig { no_ignore_parent: true, sort: Some(Sort::Files), ..ProjectConfig::default() };
+        let stored : ProjectConfig = conn.query_row("SELECT ?1",(&config,),|row| row.get(0)).unwrap();
+        assert_eq!(stored, config);
+    }
+
+    #[test]
+    fn null_rows_get_the_defaults () {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let stored : ProjectConfig = conn.query_row("SELECT NULL",(),|row| row.get(0)).unwrap();
+        assert_eq!(stored, ProjectConfig::default());
+    }
+
+    #[test]
+    fn unreadable_rows_are_a_database_error () {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        for value in ["{\"hidden\":\"yes\"}", "not json"] {
+            let stored : CoreResult<ProjectConfig> = conn.query_row("SELECT ?1",(value,),|row| row.get(0)).map_err(Into::into);
+            assert!(matches!(stored, Err(crate::error::CoreError::Db(_))));
+        }
+    }
+
+    #[test]
+    fn maps_onto_tokei_config () {
+        let config = ProjectConfig {
+            hidden: true,
+            no_ignore_dot: true,
+            types: Some(vec![LanguageType::Rust]),
+            sort: Some(Sort::Lines),
+            excluded_categories: Some(vec![Category::Data]),
+            ..ProjectConfig::default()
+        }.tokei_config();
+        assert_eq!(config.hidden, Some(true));
+        assert_eq!(config.no_ignore_dot, Some(true));
+        assert_eq!(config.no_ignore, Some(false));
+        assert_eq!(config.types, Some(vec![LanguageType::Rust]));
+        assert_eq!(config.sort, Some(Sort::Lines));
+        assert_eq!(config.categories, None);
+        assert_eq!(config.excluded_categories, Some(vec![Category::Data]));
+    }
+
+    #[test]
+    fn colors_override_those_of_tokei () {
+        let config = ProjectConfig {
+            colors: BTreeMap::from([
+                ("C++".to_string(), "#004482".to_string()),
+                ("Not a language".to_string(), "#000000".to_string()),
+            ]),
+            ..ProjectConfig::default()
+        }.tokei_config();
+        assert_eq!(config.color(LanguageType::Cpp), "#004482");
+        assert_eq!(config.color(LanguageType::Rust), LanguageType::Rust.color());
+        assert_eq!(config.languages.map(|languages| languages.len()), Some(1));
+    }
+}
@@ -0,0 +1,203 @@
+use tokei::{AuthorStats, Blame, CodeStats, Config, DirectoryTree, LanguageType, Languages, Report, Sort};
+
+use crate::models::{ModelAuthorStats, ModelBlame, ModelDirStats, ModelFilePage, ModelFileStats, ModelLangAuthors, ModelLangStats};
+
+/// One page of the files counted in a scan, for a single language or for all
+/// of them, ordered by `sort`.
+pub(crate) fn list_files (languages : &Languages,language : Option<LanguageType>,sort : Sort,offset : usize,limit : usize) -> ModelFilePage {
+    // embedded languages (code blocks in markdown, script tags in html...) count
+    // towards the file holding them, for the sort as well as the listed totals
+    let mut reports : Vec<(LanguageType, Report)> = languages.iter()
+        .filter(|(ty, _)| language.is_none_or(|l| l == **ty))
+        .flat_map(|(ty, lang)| lang.reports.iter().map(move |report| {
+            let mut summary = Report::new(report.name.clone());
+            summary.stats = report.stats.summarise();
+            (*ty, summary)
+        }))
+        .collect();
+    reports.sort_by(|a, b| sort.cmp_reports(&a.1, &b.1).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    let files = reports.iter().skip(offset).take(limit).map(|(ty, report)| ModelFileStats {
+        path: report.name.to_string_lossy().into_owned(),
+        language: ty.name().to_string(),
+        totalLinesCount: report.stats.lines(),
+        codeLinesCount: report.stats.code,
+        commentLinesCount: report.stats.comments,
+        docLinesCount: report.stats.docs,
+        blankLinesCount: report.stats.blanks,
+        complexity: report.stats.complexity,
+    }).collect();
+
+    ModelFilePage {
+        totalCount: reports.len(),
+        offset,
+        files
+    }
+}
+
+/// The directories holding the scanned files, parents before their children,
+/// starting with the scanned folders themselves at depth 0. `max_depth` of
+/// `None` lists every level.
+pub(crate) fn list_directories (languages : &Languages,folders : &[String],config : &Config,max_depth : Option<usize>) -> Vec<ModelDirStats> {
+    let tree = languages.directory_tree(folders);
+    let mut out = vec![dir_stats(config,String::new(),0,&tree)];
+    for (path, node) in tree.walk(max_depth) {
+        let depth = path.components().count();
+        out.push(dir_stats(config,path.to_string_lossy().into_owned(),depth,node));
+    }
+    out
+}
+
+fn dir_stats (config : &Config,path : String,depth : usize,node : &DirectoryTree) -> ModelDirStats {
+    let mut languages : Vec<ModelLangStats> = node.languages.iter().map(|(ty, lang)| ModelLangStats {
+        name: ty.name().to_string(),
+        color: config.color(*ty).to_string(),
+        filesCount: lang.files,
+        totalLinesCount: lang.stats.lines(),
+        codeLinesCount: lang.stats.code,
+        commentLinesCount: lang.stats.comments,
+        docLinesCount: lang.stats.docs,
+        blankLinesCount: lang.stats.blanks,
+        complexity: lang.stats.complexity,
+    }).collect();
+    languages.sort_by_key(|l| std::cmp::Reverse(l.totalLinesCount));
+    let stats : &CodeStats = &node.stats;
+    ModelDirStats {
+        path,
+        depth,
+        filesCount: node.files,
+        totalLinesCount: stats.lines(),
+        codeLinesCount: stats.code,
+        commentLinesCount: stats.comments,
+        docLinesCount: stats.docs,
+        blankLinesCount: stats.blanks,
+        languages
+    }
+}
+
+/// The authors of each language of a blamed scan, and of all of them
+/// together, the one with the most lines first.
+pub(crate) fn blame_authors (blame : &Blame,config : &Config) -> ModelBlame {
+    let languages = blame.languages.iter().map(|(ty, authors)| ModelLangAuthors {
+        name: ty.name().to_string(),
+        color: config.color(*ty).to_string(),
+        authors: authors.iter().map(author_stats).collect(),
+    }).collect();
+    ModelBlame {
+        languages,
+        authors: blame.authors().iter().map(author_stats).collect(),
+        skippedFilesCount: blame.skipped.len()
+    }
+}
+
+fn author_stats (author : &AuthorStats) -> ModelAuthorStats {
+    ModelAuthorStats {
+        name: author.name.clone(),
+        email: author.email.clone(),
+        filesCount: author.files,
+        totalLinesCount: author.stats.lines(),
+        codeLinesCount: author.stats.code,
+        commentLinesCount: author.stats.comments,
+        docLinesCount: author.stats.docs,
+        blankLinesCount: author.stats.blanks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokei::Language;
+
+    use super::*;
+
+    fn report (name : &str,code : usize,comments : usize) -> Report {
+        let mut report = Report::new(name.into());
+        report.stats.code = code;
+        report.stats.comments = comments;
+        report
+    }
+
+    fn languages () -> Languages {
+        let mut languages = Languages::new();
+        let mut rust = Language::new();
+        rust.add_report(report("src/lib.rs",120,10));
+        rust.add_report(report("src/main.rs",30,2));
+        let mut kotlin = Language::new();
+        kotlin.add_report(report("app/Main.kt",80,40));
+        languages.insert(LanguageType::Rust,rust);
+        languages.insert(LanguageType::Kotlin,kotlin);
+        languages
+    }
+
+    #[test]
+    fn lists_every_language_biggest_first () {
+        let page = list_files(&languages(),None,Sort::Code,0,10);
+        assert_eq!(page.totalCount, 3);
+        assert_eq!(page.files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["src/lib.rs", "app/Main.kt", "src/main.rs"]);
+        assert_eq!(page.files[1].language, "Kotlin");
+    }
+
+    #[test]
+    fn filters_and_paginates () {
+        let page = list_files(&languages(),Some(LanguageType::Rust),Sort::Files,1,10);
+        assert_eq!(page.totalCount, 2);
+        assert_eq!(page.offset, 1);
+        assert_eq!(page.files.len(), 1);
+        assert_eq!(page.files[0].path, "src/main.rs");
+
+        let page = list_files(&languages(),None,Sort::Comments,5,10);
+        assert_eq!(page.totalCount, 3);
+        assert!(page.files.is_empty());
+    }
+
+    #[test]
+    fn counts_embedded_languages_in_their_file () {
+        let mut languages = languages();
+        let mut readme = report("README.md",4,0);
+        readme.stats.blobs.insert(LanguageType::Rust,report("",90,3).stats);
+        let mut markdown = Language::new();
+        markdown.add_report(readme);
+        languages.insert(LanguageType::Markdown,markdown);
+
+        let page = list_files(&languages,None,Sort::Code,0,2);
+        assert_eq!(page.files[1].path, "README.md");
+        assert_eq!(page.files[1].codeLinesCount, 94);
+        assert_eq!(page.files[1].commentLinesCount, 3);
+        assert_eq!(page.files[1].totalLinesCount, 97);
+    }
+
+    #[test]
+    fn lists_directories_from_the_scanned_folder () {
+        let mut rust = Language::new();
+        rust.add_report(report("/work/proj/src/lib.rs",120,10));
+        rust.add_report(report("/work/proj/src/cli/main.rs",30,2));
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust,rust);
+
+        let dirs = list_directories(&languages,&["/work/proj".to_string()],&Config::default(),Some(1));
+
+        assert_eq!(dirs.iter().map(|d| (d.path.as_str(), d.depth, d.codeLinesCount)).collect::<Vec<_>>(), vec![("", 0, 150), ("src", 1, 150)]);
+        assert_eq!(dirs[1].languages[0].name, "Rust");
+        assert_eq!(dirs[1].languages[0].color, LanguageType::Rust.color());
+        assert_eq!(dirs[1].languages[0].filesCount, 2);
+    }
+
+    #[test]
+    fn blame_lists_authors_per_language_and_in_total () {
+        let author = |name : &str,code : usize| {
+            let mut stats = AuthorStats { name: name.to_string(), email: format!("{}@example.com", name), files: 1, ..AuthorStats::default() };
+            stats.stats.code = code;
+            stats
+        };
+        let mut blame = Blame::default();
+        blame.languages.insert(LanguageType::Rust,vec![author("alice",30),author("bob",10)]);
+        blame.languages.insert(LanguageType::Kotlin,vec![author("bob",25)]);
+        blame.skipped.push("notes.txt".into());
+
+        let model = blame_authors(&blame,&Config::default());
+
+        assert_eq!(model.languages.iter().map(|l| (l.name.as_str(), l.authors.len())).collect::<Vec<_>>(), vec![("Kotlin", 1), ("Rust", 2)]);
+        assert_eq!(model.authors.iter().map(|a| (a.name.as_str(), a.filesCount, a.codeLinesCount)).collect::<Vec<_>>(), vec![("bob", 2, 35), ("alice", 1, 30)]);
+        assert_eq!(model.authors[0].email, "bob@example.com");
+        assert_eq!(model.skippedFilesCount, 1);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+
+use tokei::{CancellationToken, Languages, ReportCache};
+
+use crate::database;
+use crate::error::{CoreError, CoreResult};
+use crate::models::{ModelScanProgress, ModelStatistics};
+use crate::progress::ScanProgress;
+use crate::project_config::ProjectConfig;
+use crate::{collect_statistics, init_lib};
+
+/// A scan running in the background, addressed from Kotlin by its handle.
+struct Scan {
+    folders : Vec<String>,
+    config : ProjectConfig,
+    progress : Arc<ScanProgress>,
+    cancellation : CancellationToken,
+    /// The project whose stored per-file results the scan reuses and updates.
+    cache : Option<(usize, Arc<ReportCache>)>,
+    worker : JoinHandle<Languages>,
+}
+
+/// The languages of a finished scan, with what it scanned and how.
+pub(crate) struct ScanResults {
+    pub languages : Languages,
+    pub folders : Vec<String>,
+    /// The settings the scan ran with, for analyses of its files.
+    pub config : ProjectConfig,
+}
+
+static SCANS: Mutex<BTreeMap<i64, Scan>> = Mutex::new(BTreeMap::new());
+/// Results of finished scans, kept for drilling down until released.
+static RESULTS: Mutex<BTreeMap<i64, Arc<ScanResults>>> = Mutex::new(BTreeMap::new());
+static NEXT_HANDLE: AtomicI64 = AtomicI64::new(1);
+
+pub(crate) fn start_scan (folders : Vec<String>,ignored : Vec<String>,config : ProjectConfig,cache : Option<(usize, Arc<ReportCache>)>) -> i64 {
+    let handle = NEXT_HANDLE.fetch_add(1,Ordering::Relaxed);
+    let progress = Arc::new(ScanProgress::default());
+    let cancellation = CancellationToken::new();
+    let worker = {
+        let folders = folders.clone();
+        let config = config.clone();
+        let progress = progress.clone();
+        let cancellation = cancellation.clone();
+        let cache = cache.as_ref().map(|(_, cache)| cache.clone());
+        thread::spawn(move || {
+            init_lib(folders.as_slice(),ignored.as_slice(),&config,cache,&progress,&cancellation)
+        })
+    };
+    SCANS.lock().unwrap().insert(handle,Scan {
+        folders,
+        config,
+        progress,
+        cancellation,
+        cache,
+        worker
+    });
+    handle
+}
+
+/// Asks the scan to stop, returns `false` if there is no such scan.
+pub(crate) fn cancel_scan (handle : i64) -> bool {
+    match SCANS.lock().unwrap().get(&handle) {
+        Some(scan) => {
+            scan.cancellation.cancel();
+            true
+        }
+        None => false
+    }
+}
+
+pub(crate) fn poll_scan (handle : i64) -> Option<ModelScanProgress> {
+    let scans = SCANS.lock().unwrap();
+    let scan = scans.get(&handle)?;
+    let mut progress = scan.progress.snapshot();
+    progress.finished = scan.worker.is_finished();
+    progress.cancelled = scan.cancellation.is_cancelled();
+    Some(progress)
+}
+
+/// Waits for the scan to end. The statistics of a scan that was cancelled
+/// before it finished only cover the files parsed before it stopped and are
+/// marked as incomplete. The per-file results stay available under the same
+/// handle until `release_scan`. Failing to store them for the next scan
+/// doesn't fail this one, it's reported in `cacheError` instead.
+pub(crate) fn finish_scan (handle : i64) -> CoreResult<ModelStatistics> {
+    let scan = SCANS.lock().unwrap().remove(&handle)
+        .ok_or_else(|| CoreError::InvalidArgument(format!("no scan with handle {}", handle)))?;
+    let languages = scan.worker.join().map_err(CoreError::from_panic)?;
+    let mut statistics = collect_statistics(&languages,&scan.folders,&scan.config,scan.cancellation.stopped_early())?;
+    if let Some((p_id, cache)) = &scan.cache {
+        statistics.cacheError = database::write_file_cache(*p_id,cache).err().map(|e| e.to_string());
+    }
+    RESULTS.lock().unwrap().insert(handle,Arc::new(ScanResults {
+        languages,
+        folders: scan.folders,
+        config: scan.config
+    }));
+    Ok(statistics)
+}
+
+/// The results of a finished scan.
+pub(crate) fn scan_results (handle : i64) -> CoreResult<Arc<ScanResults>> {
+    RESULTS.lock().unwrap().get(&handle).cloned()
+        .ok_or_else(|| CoreError::InvalidArgument(format!("no finished scan with handle {}", handle)))
+}
+
+/// Drops the results of a finished scan, or cancels and forgets one that is
+/// still running, returns `false` if there was neither.
+pub(crate) fn release_scan (handle : i64) -> bool {
+    if let Some(scan) = SCANS.lock().unwrap().remove(&handle) {
+        // the worker stops on its own once it sees the cancellation
+        scan.cancellation.cancel();
+        return true;
+    }
+    RESULTS.lock().unwrap().remove(&handle).is_some()
+}
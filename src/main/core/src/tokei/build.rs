@@ -24,13 +24,25 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<dyn error::Error>> {
     let json_string: String = fs::read_to_string("languages.json")?.parse()?;
     let mut json: Value = json5::from_str(&json_string)?;
 
-    for (_key, ref mut item) in json
+    for (key, ref mut item) in json
         .get_mut("languages")
         .unwrap()
         .as_object_mut()
         .unwrap()
         .iter_mut()
     {
+        match item.get("category").and_then(Value::as_str) {
+            Some("programming" | "markup" | "data" | "prose") => {}
+            category => {
+                return Err(format!("{} has an unknown category: {:?}", key, category).into())
+            }
+        }
+
+        match item.get("color").and_then(Value::as_str) {
+            Some(color) if is_hex_color(color) => {}
+            color => return Err(format!("{} has an invalid color: {:?}", key, color).into()),
+        }
+
         macro_rules! sort_prop {
             ($prop:expr) => {{
                 if let Some(ref mut prop) = item.get_mut($prop) {
@@ -56,6 +68,11 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<dyn error::Error>> {
     Ok(())
 }
 
+/// Whether `color` is written as `#RRGGBB`.
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
 fn compare_json_str_len(a: &Value, b: &Value) -> cmp::Ordering {
     let a = a.as_array().expect("a as array");
     let b = b.as_array().expect("b as array");
@@ -0,0 +1,157 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{utils, CodeStats, Config, LanguageType, Languages};
+
+/// The lines of a language that one author changed last.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct AuthorStats {
+    /// The author's name, or [`AuthorStats::NOT_COMMITTED`] for lines that
+    /// differ from `HEAD`.
+    pub name: String,
+    /// The author's email, empty for lines that differ from `HEAD`.
+    pub email: String,
+    /// The number of files with at least one line by the author.
+    pub files: usize,
+    /// The lines by the author. Lines of a language embedded in a file are
+    /// counted as code of the file's own language.
+    pub stats: CodeStats,
+}
+
+impl AuthorStats {
+    /// The name given to lines that were added or changed since `HEAD`, as
+    /// `git blame` does.
+    pub const NOT_COMMITTED: &'static str = "Not Committed Yet";
+
+    /// Whether these are the lines changed since `HEAD`.
+    #[must_use]
+    pub fn is_not_committed(&self) -> bool {
+        self.name == Self::NOT_COMMITTED && self.email.is_empty()
+    }
+}
+
+/// The lines of counted files split by the author who changed them last,
+/// see [`Languages::blame`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Blame {
+    /// The authors of each language, the one with the most lines first.
+    pub languages: BTreeMap<LanguageType, Vec<AuthorStats>>,
+    /// Files that couldn't be blamed, such as those outside of a git
+    /// repository.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl Blame {
+    /// The authors of every language together, the one with the most lines
+    /// first.
+    #[must_use]
+    pub fn authors(&self) -> Vec<AuthorStats> {
+        let mut authors = Vec::new();
+        for stats in self.languages.values().flatten() {
+            add_author(
+                &mut authors,
+                &stats.name,
+                &stats.email,
+                stats.files,
+                &stats.stats,
+            );
+        }
+        sort(&mut authors);
+        authors
+    }
+
+    /// Adds the lines of one file of `language`, keyed by author name and
+    /// email.
+    pub(crate) fn add_file(
+        &mut self,
+        language: LanguageType,
+        authors: BTreeMap<(String, String), CodeStats>,
+    ) {
+        let entry = self.languages.entry(language).or_default();
+        for ((name, email), stats) in authors {
+            add_author(entry, &name, &email, 1, &stats);
+        }
+    }
+
+    pub(crate) fn skip(&mut self, path: &Path) {
+        self.skipped.push(path.to_owned());
+    }
+
+    /// Puts the authors of each language and the skipped files in order.
+    pub(crate) fn sort(&mut self) {
+        self.languages
+            .values_mut()
+            .for_each(|authors| sort(authors));
+        self.skipped.sort();
+    }
+}
+
+fn add_author(
+    authors: &mut Vec<AuthorStats>,
+    name: &str,
+    email: &str,
+    files: usize,
+    stats: &CodeStats,
+) {
+    let index = match authors
+        .iter()
+        .position(|a| a.name == name && a.email == email)
+    {
+        Some(index) => index,
+        None => {
+            authors.push(AuthorStats {
+                name: name.to_owned(),
+                email: email.to_owned(),
+                ..AuthorStats::default()
+            });
+            authors.len() - 1
+        }
+    };
+    authors[index].files += files;
+    authors[index].stats += stats;
+}
+
+fn sort(authors: &mut [AuthorStats]) {
+    authors.sort_by(|a, b| {
+        b.stats
+            .lines()
+            .cmp(&a.stats.lines())
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.email.cmp(&b.email))
+    });
+}
+
+impl Languages {
+    /// Runs `git blame` on every counted file as it is in the working tree,
+    /// splitting its code, comment and blank lines by the author who changed
+    /// them last. Lines that differ from `HEAD`, including those of files
+    /// that aren't committed, are given to [`AuthorStats::NOT_COMMITTED`].
+    /// Only local repositories are read, and mailmaps are honoured.
+    ///
+    /// ```no_run
+    /// use tokei::{Config, Languages};
+    ///
+    /// let config = Config::default();
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["src"], &[], &config);
+    ///
+    /// for author in languages.blame(&config).authors() {
+    ///     println!("{} <{}>: {}", author.name, author.email, author.stats.code);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn blame(&self, config: &Config) -> Blame {
+        let files = self
+            .iter()
+            .flat_map(|(ty, language)| {
+                language
+                    .reports
+                    .iter()
+                    .map(move |report| (report.name.clone(), *ty))
+            })
+            .collect();
+        utils::git::blame_files(files, config)
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use dashmap::{DashMap, DashSet};
+
+use crate::{
+    classify::FileClass,
+    config::Config,
+    language::LanguageType,
+    stats::{CodeStats, Report},
+};
+
+/// The shape of the statistics kept for a file. Bump it whenever
+/// [`CacheEntry`] or [`CodeStats`] gain a field, so that older caches, whose
+/// entries would read the new field as its default, are dropped.
+const CACHE_FORMAT: u32 = 1;
+
+/// What a file looked like on disk when it was last parsed.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FileStamp {
+    /// Modification time in nanoseconds since the Unix epoch.
+    pub modified: u64,
+    /// Size in bytes.
+    pub size: u64,
+    /// Hash of the contents, only present when the cache was created with
+    /// [`ReportCache::with_content_hash`].
+    pub hash: Option<u64>,
+}
+
+impl FileStamp {
+    /// Reads the stamp of the file at `path`, hashing its contents if
+    /// `content_hash` is set.
+    pub fn read(path: &Path, content_hash: bool) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        let hash = if content_hash {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(&fs::read(path)?);
+            Some(hasher.finish())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            modified,
+            size: metadata.len(),
+            hash,
+        })
+    }
+
+    /// Whether a file with this stamp still has the contents of one stamped
+    /// `cached`. Files touched without being changed are recognised when both
+    /// stamps carry a content hash.
+    #[must_use]
+    pub fn matches(&self, cached: &FileStamp) -> bool {
+        self.size == cached.size
+            && (self.modified == cached.modified || self.hash.is_some() && self.hash == cached.hash)
+    }
+}
+
+/// The cached result of parsing a single file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CacheEntry {
+    /// The language the file was parsed as.
+    pub language: LanguageType,
+    /// The file as it was when parsed.
+    pub stamp: FileStamp,
+    /// The statistics found in the file.
+    pub stats: CodeStats,
+    /// The class of the file going by its contents.
+    #[serde(default)]
+    pub class: Option<FileClass>,
+}
+
+/// A per-file cache of parse results, set as [`Config::cache`] so that a
+/// search only parses files that changed since the previous one.
+///
+/// The cache is filled while searching, and once a search finishes without
+/// being cancelled any file that wasn't found again is dropped from it.
+/// Content hashes use the standard library's default hasher, so a cache
+/// written by a different build may reparse files it already had.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use tokei::{Config, Languages, ReportCache};
+///
+/// let mut config = Config::default();
+/// let cache = Arc::new(ReportCache::load("tokei-cache.json".as_ref(), &config));
+/// config.cache = Some(cache.clone());
+///
+/// let mut languages = Languages::new();
+/// languages.get_statistics(&["src"], &[], &config);
+/// cache.save("tokei-cache.json".as_ref()).unwrap();
+/// ```
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReportCache {
+    version: String,
+    format: u32,
+    doc_strings_as_comments: bool,
+    custom_languages: u64,
+    content_hash: bool,
+    entries: DashMap<PathBuf, CacheEntry>,
+    #[serde(skip)]
+    seen: DashSet<PathBuf>,
+}
+
+impl ReportCache {
+    /// An empty cache for searches run with `config`.
+    #[must_use]
+    pub fn new(config: &Config) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            format: CACHE_FORMAT,
+            doc_strings_as_comments: doc_strings_as_comments(config),
+            custom_languages: custom_languages(config),
+            content_hash: false,
+            entries: DashMap::new(),
+            seen: DashSet::new(),
+        }
+    }
+
+    /// Also keys files by a hash of their contents, which costs a read of
+    /// every file but survives tools that touch files without changing them.
+    #[must_use]
+    pub fn with_content_hash(mut self, content_hash: bool) -> Self {
+        if content_hash != self.content_hash {
+            self.entries.clear();
+        }
+        self.content_hash = content_hash;
+        self
+    }
+
+    /// Reads a cache written by [`ReportCache::save`]. A missing or
+    /// unreadable file, or one written by another version of tokei or for
+    /// other counting options or custom languages, gives an empty cache.
+    #[must_use]
+    pub fn load(path: &Path, config: &Config) -> Self {
+        config.register_custom_languages();
+        let cache = fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok());
+
+        match cache {
+            Some(cache) if cache.is_compatible(config) => cache,
+            _ => Self::new(config),
+        }
+    }
+
+    /// Writes the cache to `path`, creating its parent directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+
+    /// The number of cached files.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no file is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// A copy of every cached file and its entry.
+    #[must_use]
+    pub fn entries(&self) -> Vec<(PathBuf, CacheEntry)> {
+        self.entries
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect()
+    }
+
+    /// Adds or replaces the entry of a file.
+    pub fn insert(&self, path: PathBuf, entry: CacheEntry) {
+        self.entries.insert(path, entry);
+    }
+
+    fn is_compatible(&self, config: &Config) -> bool {
+        self.version == env!("CARGO_PKG_VERSION")
+            && self.format == CACHE_FORMAT
+            && self.doc_strings_as_comments == doc_strings_as_comments(config)
+            && self.custom_languages == custom_languages(config)
+    }
+
+    /// Drops every entry if the counting options changed since the cache was
+    /// filled, and starts tracking which files are found.
+    pub(crate) fn prepare(&self, config: &Config) {
+        if !self.is_compatible(config) {
+            self.entries.clear();
+        }
+        self.seen.clear();
+    }
+
+    /// Parses the file at `path` unless it is unchanged since it was cached.
+    pub(crate) fn parse(
+        &self,
+        language: LanguageType,
+        path: PathBuf,
+        config: &Config,
+    ) -> Result<Report, (io::Error, PathBuf)> {
+        self.seen.insert(path.clone());
+
+        let stamp = match FileStamp::read(&path, self.content_hash) {
+            Ok(stamp) => stamp,
+            Err(error) => return Err((error, path)),
+        };
+
+        if let Some(entry) = self.entries.get(&path) {
+            if entry.language == language && stamp.matches(&entry.stamp) {
+                let mut report = Report::new(path.clone());
+                report.stats = entry.stats.clone();
+                report.class = entry.class;
+                return Ok(report);
+            }
+        }
+
+        let report = language.parse(path, config)?;
+        self.entries.insert(
+            report.name.clone(),
+            CacheEntry {
+                language,
+                stamp,
+                stats: report.stats.clone(),
+                class: report.class,
+            },
+        );
+        Ok(report)
+    }
+
+    /// Drops the entries of files that weren't found by the last search.
+    pub(crate) fn retain_seen(&self) {
+        self.entries.retain(|path, _| self.seen.contains(path));
+        self.seen.clear();
+    }
+}
+
+fn doc_strings_as_comments(config: &Config) -> bool {
+    config.treat_doc_strings_as_comments.unwrap_or(false)
+}
+
+/// A fingerprint of the custom languages of `config`, so that editing their
+/// definitions reparses the files counted as them.
+fn custom_languages(config: &Config) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.custom_languages.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::language::Languages;
+
+    fn count(dir: &TempDir, config: &Config) -> usize {
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], config);
+        languages
+            .get(&LanguageType::Rust)
+            .map_or(0, |rust| rust.code)
+    }
+
+    fn config_with(cache: &Arc<ReportCache>) -> Config {
+        Config {
+            cache: Some(cache.clone()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn reuses_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+        let cache = Arc::new(ReportCache::new(&Config::default()));
+        let config = config_with(&cache);
+
+        assert_eq!(count(&dir, &config), 1);
+
+        let mut entry = cache.entries.get(&path).unwrap().clone();
+        entry.stats.code = 42;
+        cache.insert(path.clone(), entry);
+
+        assert_eq!(count(&dir, &config), 42);
+    }
+
+    #[test]
+    fn reparses_changed_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+        let cache = Arc::new(ReportCache::new(&Config::default()));
+        let config = config_with(&cache);
+        count(&dir, &config);
+
+        fs::write(&path, "fn main() {}\nfn other() {}\n").unwrap();
+
+        assert_eq!(count(&dir, &config), 2);
+    }
+
+    #[test]
+    fn drops_deleted_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn lib() {}\n").unwrap();
+        let cache = Arc::new(ReportCache::new(&Config::default()));
+        let config = config_with(&cache);
+        count(&dir, &config);
+        assert_eq!(cache.len(), 2);
+
+        fs::remove_file(dir.path().join("lib.rs")).unwrap();
+        count(&dir, &config);
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn touched_files_match_by_content_hash() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+        let stamp = FileStamp::read(&path, true).unwrap();
+        let touched = FileStamp {
+            modified: stamp.modified + 1,
+            ..stamp
+        };
+
+        assert!(touched.matches(&stamp));
+        assert!(!FileStamp {
+            hash: None,
+            ..touched
+        }
+        .matches(&stamp));
+    }
+
+    #[test]
+    fn saved_cache_is_reloaded_for_the_same_options() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        let file = dir.path().join("cache").join("tokei.json");
+        let cache = Arc::new(ReportCache::new(&Config::default()));
+        count(&dir, &config_with(&cache));
+        cache.save(&file).unwrap();
+
+        assert_eq!(ReportCache::load(&file, &Config::default()).len(), 1);
+
+        let doc_comments = Config {
+            treat_doc_strings_as_comments: Some(true),
+            ..Config::default()
+        };
+        assert!(ReportCache::load(&file, &doc_comments).is_empty());
+    }
+
+    #[test]
+    fn caches_of_other_formats_are_dropped() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        let file = dir.path().join("tokei-cache.json");
+        let cache = Arc::new(ReportCache::new(&Config::default()));
+        count(&dir, &config_with(&cache));
+        let mut json: serde_json::Value = serde_json::to_value(&*cache).unwrap();
+        json["format"] = (CACHE_FORMAT - 1).into();
+        fs::write(&file, json.to_string()).unwrap();
+
+        assert!(ReportCache::load(&file, &Config::default()).is_empty());
+
+        json.as_object_mut().unwrap().remove("format");
+        fs::write(&file, json.to_string()).unwrap();
+
+        assert!(ReportCache::load(&file, &Config::default()).is_empty());
+    }
+
+    #[test]
+    fn custom_languages_are_reloaded_until_redefined() {
+        let custom = |line_comment: &str| -> Config {
+            toml::from_str(&format!(
+                "[custom_languages.Flow]\nextensions = [\"flow\"]\nline_comment = [\"{}\"]\n",
+                line_comment
+            ))
+            .unwrap()
+        };
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("main.flow"), "-- note\nrun\n").unwrap();
+        let file = dir.path().join("tokei-cache.json");
+        let cache = Arc::new(ReportCache::new(&custom("--")));
+        let config = Config {
+            cache: Some(cache.clone()),
+            ..custom("--")
+        };
+        count(&dir, &config);
+        cache.save(&file).unwrap();
+
+        let reloaded = ReportCache::load(&file, &custom("--"));
+
+        assert_eq!(reloaded.len(), 2);
+        assert!(reloaded
+            .entries()
+            .iter()
+            .any(|(_, entry)| entry.language.name() == "Flow"));
+        assert!(ReportCache::load(&file, &custom("#")).is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A flag shared between a running [`Languages::get_statistics`] call and
+/// whoever started it, allowing the search to be stopped early. Once
+/// cancelled, no more files are walked or parsed and the statistics gathered
+/// so far are kept.
+///
+/// ```
+/// use tokei::{CancellationToken, Config, Languages};
+///
+/// let token = CancellationToken::new();
+/// let config = Config {
+///     cancellation: Some(token.clone()),
+///     ..Config::default()
+/// };
+///
+/// token.cancel();
+///
+/// let mut languages = Languages::new();
+/// languages.get_statistics(&["."], &[], &config);
+/// assert!(languages.is_empty());
+/// ```
+///
+/// [`Languages::get_statistics`]: struct.Languages.html#method.get_statistics
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<State>);
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: AtomicBool,
+    stopped: AtomicBool,
+}
+
+impl CancellationToken {
+    /// Creates a new token that hasn't been cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests any search using this token to stop.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`CancellationToken::cancel`] has been called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether a search using this token left files out because it was
+    /// cancelled. A search cancelled only after it had finished counted
+    /// everything.
+    #[must_use]
+    pub fn stopped_early(&self) -> bool {
+        self.0.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Whether the search should stop, recording that it did if so.
+    pub(crate) fn stop_if_cancelled(&self) -> bool {
+        let cancelled = self.is_cancelled();
+        if cancelled {
+            self.0.stopped.store(true, Ordering::Relaxed);
+        }
+        cancelled
+    }
+}
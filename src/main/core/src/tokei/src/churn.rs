@@ -0,0 +1,334 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    tree::{directory_names, relative_to_roots},
+    utils, CodeStats, Config, LanguageType, Languages,
+};
+
+/// How often a file or directory changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Churn {
+    /// The number of commits that changed it.
+    pub commits: usize,
+    /// The lines those commits added.
+    pub added: usize,
+    /// The lines those commits removed.
+    pub removed: usize,
+}
+
+impl Churn {
+    /// The lines changed, added and removed together.
+    #[must_use]
+    pub fn lines(&self) -> usize {
+        self.added + self.removed
+    }
+}
+
+/// A file or directory with its churn and its current size.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Hotspot {
+    /// The report's name for a file, or the directory's path relative to
+    /// the counted paths.
+    pub name: PathBuf,
+    /// The language of a file, `None` for directories.
+    pub language: Option<LanguageType>,
+    /// The number of files, 1 for a file.
+    pub files: usize,
+    /// The current lines, embedded languages included.
+    pub stats: CodeStats,
+    /// The changes made within the time window.
+    pub churn: Churn,
+}
+
+/// What hotspots are ranked by, the most changed first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HotspotRank {
+    /// The number of commits.
+    #[default]
+    Commits,
+    /// The lines added and removed.
+    Lines,
+}
+
+impl FromStr for HotspotRank {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "commits" => Ok(HotspotRank::Commits),
+            "lines" => Ok(HotspotRank::Lines),
+            s => Err(format!("Unsupported hotspot rank: {}", s)),
+        }
+    }
+}
+
+/// The files and directories that changed the most in a time window, with
+/// their current size, see [`Languages::hotspots`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Hotspots {
+    /// The start of the window in seconds since the Unix epoch, `None` for
+    /// the whole history.
+    pub since: Option<i64>,
+    /// The end of the window in seconds since the Unix epoch, `None` for
+    /// now.
+    pub until: Option<i64>,
+    /// Every counted file, the most changed first.
+    pub files: Vec<Hotspot>,
+    /// Every directory holding counted files, the most changed first.
+    pub directories: Vec<Hotspot>,
+    /// Files outside of a git repository, which have no history.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl Hotspots {
+    /// Ranks the files and directories by `rank`, breaking ties by their
+    /// lines of code so that big files that change often come first.
+    pub fn sort_by(&mut self, rank: HotspotRank) {
+        let key = |hotspot: &Hotspot| {
+            let changes = match rank {
+                HotspotRank::Commits => hotspot.churn.commits,
+                HotspotRank::Lines => hotspot.churn.lines(),
+            };
+            (changes, hotspot.stats.code)
+        };
+        for hotspots in [&mut self.files, &mut self.directories] {
+            hotspots.sort_by(|a, b| key(b).cmp(&key(a)).then_with(|| a.name.cmp(&b.name)));
+        }
+    }
+}
+
+impl Languages {
+    /// Reads the history of the git repositories the counted files are in,
+    /// and finds the churn of every file and directory within `since..until`
+    /// (in seconds since the Unix epoch) alongside its current size.
+    /// Directories are named relative to `roots` like in
+    /// [`Languages::directory_tree`]. Merge commits are left out, renames
+    /// are followed, and hotspots are ranked by [`HotspotRank::Commits`].
+    ///
+    /// ```no_run
+    /// use tokei::{Config, HotspotRank, Languages};
+    ///
+    /// let config = Config::default();
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["src"], &[], &config);
+    ///
+    /// let mut hotspots = languages.hotspots(&["src"], None, None, &config);
+    /// hotspots.sort_by(HotspotRank::Lines);
+    /// for file in hotspots.files.iter().take(10) {
+    ///     println!("{}: {}", file.name.display(), file.churn.lines());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn hotspots<P: AsRef<Path>>(
+        &self,
+        roots: &[P],
+        since: Option<i64>,
+        until: Option<i64>,
+        config: &Config,
+    ) -> Hotspots {
+        let mut files = self
+            .iter()
+            .flat_map(|(ty, language)| {
+                language.reports.iter().map(move |report| Hotspot {
+                    name: report.name.clone(),
+                    language: Some(*ty),
+                    files: 1,
+                    stats: report.stats.summarise(),
+                    churn: Churn::default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let names = files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        let (commits, outside) = utils::git::file_changes(&names, since, until, config);
+
+        let directories_of = files
+            .iter()
+            .map(|file| {
+                let parent = relative_to_roots(&file.name, roots)
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""));
+                directory_names(parent)
+                    .into_iter()
+                    .scan(PathBuf::new(), |path, name| {
+                        path.push(name);
+                        Some(path.clone())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let outside = outside.into_iter().collect::<BTreeSet<_>>();
+
+        let mut directories = BTreeMap::<PathBuf, Hotspot>::new();
+        for (index, file) in files.iter().enumerate() {
+            if outside.contains(&index) {
+                continue;
+            }
+            for path in &directories_of[index] {
+                let directory = directories.entry(path.clone()).or_default();
+                directory.files += 1;
+                directory.stats += &file.stats;
+            }
+        }
+
+        for changes in commits {
+            let mut changed = BTreeMap::<&PathBuf, Churn>::new();
+            for (index, added, removed) in changes {
+                let churn = &mut files[index].churn;
+                churn.commits += 1;
+                churn.added += added;
+                churn.removed += removed;
+                for path in &directories_of[index] {
+                    let churn = changed.entry(path).or_default();
+                    churn.added += added;
+                    churn.removed += removed;
+                }
+            }
+            for (path, churn) in changed {
+                let directory = &mut directories.get_mut(path).unwrap().churn;
+                directory.commits += 1;
+                directory.added += churn.added;
+                directory.removed += churn.removed;
+            }
+        }
+
+        let mut skipped = Vec::new();
+        let files = files
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, file)| {
+                if outside.contains(&index) {
+                    skipped.push(file.name);
+                    None
+                } else {
+                    Some(file)
+                }
+            })
+            .collect();
+
+        let mut hotspots = Hotspots {
+            since,
+            until,
+            files,
+            directories: directories
+                .into_iter()
+                .map(|(name, hotspot)| Hotspot { name, ..hotspot })
+                .collect(),
+            skipped,
+        };
+        hotspots.sort_by(HotspotRank::Commits);
+        hotspots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use git2::{Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn commit_at(repo: &Repository, seconds: i64) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::FORCE, None)
+            .unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature =
+            Signature::new("tokei", "tokei@example.com", &Time::new(seconds, 0)).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "change",
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    fn repository() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("old.rs"), "fn a() {}\n").unwrap();
+        fs::write(src.join("b.rs"), "fn b() {}\n").unwrap();
+        fs::write(dir.path().join("build.rs"), "fn main() {}\n").unwrap();
+        commit_at(&repo, 1_000);
+        fs::write(src.join("old.rs"), "fn a() {}\nfn c() {}\n").unwrap();
+        commit_at(&repo, 2_000);
+        fs::rename(src.join("old.rs"), src.join("a.rs")).unwrap();
+        commit_at(&repo, 3_000);
+        fs::write(src.join("a.rs"), "fn a() {}\nfn d() {}\n").unwrap();
+        commit_at(&repo, 4_000);
+        dir
+    }
+
+    fn hotspots(dir: &TempDir, since: Option<i64>, until: Option<i64>) -> Hotspots {
+        let config = Config::default();
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], &config);
+        languages.hotspots(&[dir.path()], since, until, &config)
+    }
+
+    fn names(hotspots: &[Hotspot]) -> Vec<(String, usize)> {
+        hotspots
+            .iter()
+            .map(|h| {
+                let name = h.name.file_name().unwrap().to_string_lossy().into_owned();
+                (name, h.churn.commits)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn follows_renames_and_rolls_up_directories() {
+        let dir = repository();
+        let hotspots = hotspots(&dir, None, None);
+
+        assert_eq!(
+            names(&hotspots.files),
+            [
+                ("a.rs".into(), 4),
+                ("build.rs".into(), 1),
+                ("b.rs".into(), 1)
+            ]
+        );
+        assert_eq!(hotspots.files[0].churn.added, 3);
+        assert_eq!(hotspots.files[0].churn.removed, 1);
+        assert_eq!(hotspots.files[0].stats.code, 2);
+
+        assert_eq!(names(&hotspots.directories), [("src".into(), 4)]);
+        assert_eq!(hotspots.directories[0].files, 2);
+        assert_eq!(hotspots.directories[0].churn.lines(), 5);
+        assert!(hotspots.skipped.is_empty());
+    }
+
+    #[test]
+    fn only_counts_commits_in_the_window() {
+        let dir = repository();
+        let mut hotspots = hotspots(&dir, Some(2_000), Some(4_000));
+        hotspots.sort_by(HotspotRank::Lines);
+
+        assert_eq!(
+            names(&hotspots.files),
+            [
+                ("a.rs".into(), 2),
+                ("build.rs".into(), 0),
+                ("b.rs".into(), 0)
+            ]
+        );
+        assert_eq!(hotspots.files[0].churn.lines(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use crate::LanguageType;
+
+/// Directories holding copies of other projects' code.
+const VENDOR_DIRECTORIES: &[&str] = &[
+    "bower_components",
+    "carthage",
+    "jspm_packages",
+    "node_modules",
+    "pods",
+    "third-party",
+    "third_party",
+    "thirdparty",
+    "vendor",
+    "vendors",
+];
+
+/// Files written by package managers.
+const LOCKFILES: &[&str] = &[
+    "bun.lock",
+    "cargo.lock",
+    "composer.lock",
+    "flake.lock",
+    "gemfile.lock",
+    "go.sum",
+    "mix.lock",
+    "package-lock.json",
+    "packages.lock.json",
+    "pipfile.lock",
+    "pnpm-lock.yaml",
+    "podfile.lock",
+    "poetry.lock",
+    "pubspec.lock",
+    "yarn.lock",
+];
+
+/// Markers left by code generators near the top of their output.
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "Code generated by"];
+
+/// How many lines from the top of a file are searched for markers.
+const MARKER_LINES: usize = 5;
+
+/// Languages whose files are commonly minified.
+const MINIFIABLE: &[LanguageType] = &[
+    LanguageType::Css,
+    LanguageType::JavaScript,
+    LanguageType::Json,
+];
+
+/// The average line length above which a file is taken to be minified.
+const MINIFIED_LINE_LEN: usize = 110;
+
+/// Why a file isn't counted with the code written in a project, see
+/// [`Report::class`](crate::Report::class).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum FileClass {
+    /// Written by a tool, such as lockfiles, protobuf output and minified
+    /// bundles.
+    Generated,
+    /// Copied from another project, such as the contents of `vendor/` or
+    /// `node_modules/`.
+    Vendored,
+}
+
+impl FileClass {
+    /// Classifies `path` by its file name and the `depth - 1` directories
+    /// above it, those within the searched directory, so that searching a
+    /// project that itself lives below `vendor/` still counts it.
+    pub(crate) fn of_path(path: &Path, depth: usize) -> Option<Self> {
+        let mut components = path.components().rev().take(depth.max(1));
+        let filename = components.next()?.as_os_str().to_string_lossy();
+        let mut directories = components.map(|dir| dir.as_os_str().to_string_lossy());
+
+        if directories.any(|dir| VENDOR_DIRECTORIES.contains(&&*dir.to_lowercase())) {
+            Some(FileClass::Vendored)
+        } else if LOCKFILES.contains(&&*filename.to_lowercase()) {
+            Some(FileClass::Generated)
+        } else {
+            None
+        }
+    }
+
+    /// Classifies a file of `language` by its contents, looking for the
+    /// markers of code generators and for minified code.
+    pub(crate) fn of_content(language: LanguageType, text: &[u8]) -> Option<Self> {
+        let marked = text.split(|&b| b == b'\n').take(MARKER_LINES).any(|line| {
+            GENERATED_MARKERS
+                .iter()
+                .any(|marker| line.windows(marker.len()).any(|w| w == marker.as_bytes()))
+        });
+
+        (marked || (MINIFIABLE.contains(&language) && is_minified(text)))
+            .then_some(FileClass::Generated)
+    }
+}
+
+/// Whether the non-blank lines of `text` are too long on average to have
+/// been written by hand.
+fn is_minified(text: &[u8]) -> bool {
+    let (lines, len) = text
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.trim_ascii().is_empty())
+        .fold((0, 0), |(lines, len), line| (lines + 1, len + line.len()));
+
+    lines > 0 && len / lines > MINIFIED_LINE_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vendor_directories_within_the_search_are_vendored() {
+        let path = Path::new("/home/vendor/app/node_modules/left-pad/index.js");
+
+        assert_eq!(FileClass::of_path(path, 3), Some(FileClass::Vendored));
+        assert_eq!(FileClass::of_path(path, 2), None);
+        assert_eq!(FileClass::of_path(Path::new("app/src/vendor.rs"), 3), None);
+        assert_eq!(
+            FileClass::of_path(Path::new("app/Cargo.lock"), 2),
+            Some(FileClass::Generated)
+        );
+    }
+
+    #[test]
+    fn marked_and_minified_files_are_generated() {
+        let protobuf = b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n";
+        let minified = format!("!function(){{{}}}();\n", "var a=1;".repeat(40));
+
+        assert_eq!(
+            FileClass::of_content(LanguageType::Go, protobuf),
+            Some(FileClass::Generated)
+        );
+        assert_eq!(
+            FileClass::of_content(LanguageType::JavaScript, minified.as_bytes()),
+            Some(FileClass::Generated)
+        );
+        assert_eq!(
+            FileClass::of_content(LanguageType::Markdown, minified.as_bytes()),
+            None
+        );
+        assert_eq!(
+            FileClass::of_content(LanguageType::Rust, b"fn main() {}\n"),
+            None
+        );
+    }
+}
@@ -1,13 +1,19 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::mem;
+use std::path::PathBuf;
 use std::process;
+use std::sync::Arc;
 
 use clap::Arg;
 use clap::{crate_description, ArgMatches};
 use colored::Colorize;
-use tokei::{Config, LanguageType, Sort};
+use etcetera::BaseStrategy;
+use tokei::{Category, Config, HotspotRank, LanguageType, Report, Sort};
 
 use crate::{
-    cli_utils::{crate_version, parse_or_exit, NumberFormatStyle},
+    cli_utils::{crate_version, parse_or_exit, NumberFormatStyle, Timestamp},
     input::Format,
 };
 
@@ -42,13 +48,24 @@ pub struct Cli {
     pub no_ignore_parent: bool,
     pub no_ignore_dot: bool,
     pub no_ignore_vcs: bool,
+    pub skip_generated: bool,
     pub output: Option<Format>,
+    pub by_dir: Option<usize>,
+    pub revision: Option<String>,
+    pub diff: Option<(String, String)>,
+    pub blame: bool,
+    pub hotspots: Option<HotspotRank>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
     pub streaming: Option<Streaming>,
     pub print_languages: bool,
     pub sort: Option<Sort>,
     pub sort_reverse: bool,
     pub types: Option<Vec<LanguageType>>,
+    pub categories: Option<Vec<Category>>,
+    pub excluded_categories: Option<Vec<Category>>,
     pub compact: bool,
+    pub cache_hash: bool,
     pub number_format: num_format::CustomFormat,
     pub verbose: u64,
 }
@@ -132,6 +149,10 @@ impl Cli {
                 "Don't respect VCS ignore files (.gitignore, .hgignore, etc.) including \
                 those in parent directories.",
             ))
+            .arg(Arg::new("skip_generated").long("skip-generated").help(
+                "Don't count generated and vendored files, such as lockfiles, minified \
+                bundles and the contents of vendor/ and node_modules/.",
+            ))
             .arg(
                 Arg::new("output")
                     .long("output")
@@ -150,7 +171,7 @@ impl Cli {
                     .possible_values(&["simple", "json"])
                     .ignore_case(true)
                     .help(
-                        "prints the (language, path, lines, blanks, code, comments) records as \
+                        "prints the (language, path, lines, blanks, code, comments, docs) records as \
                         simple lines or as Json for batch processing",
                     ),
             )
@@ -159,7 +180,15 @@ impl Cli {
                     .long("sort")
                     .short('s')
                     .takes_value(true)
-                    .possible_values(&["files", "lines", "blanks", "code", "comments"])
+                    .possible_values(&[
+                        "files",
+                        "lines",
+                        "blanks",
+                        "code",
+                        "comments",
+                        "docs",
+                        "complexity",
+                    ])
                     .ignore_case(true)
                     .conflicts_with("rsort")
                     .help("Sort languages based on column"),
@@ -169,7 +198,15 @@ impl Cli {
                     .long("rsort")
                     .short('r')
                     .takes_value(true)
-                    .possible_values(&["files", "lines", "blanks", "code", "comments"])
+                    .possible_values(&[
+                        "files",
+                        "lines",
+                        "blanks",
+                        "code",
+                        "comments",
+                        "docs",
+                        "complexity",
+                    ])
                     .ignore_case(true)
                     .conflicts_with("sort")
                     .help("Reverse sort languages based on column"),
@@ -184,6 +221,132 @@ impl Cli {
                         -t=Rust,Markdown",
                     ),
             )
+            .arg(
+                Arg::new("categories")
+                    .long("categories")
+                    .takes_value(true)
+                    .help(
+                        "Filters output by language category, one of programming, markup, data \
+                        or prose, separated by a comma. i.e. --categories=programming,markup",
+                    ),
+            )
+            .arg(
+                Arg::new("excluded_categories")
+                    .long("exclude-categories")
+                    .takes_value(true)
+                    .help(
+                        "Leaves out the languages of the categories, separated by a comma. i.e. \
+                        --exclude-categories=data,prose",
+                    ),
+            )
+            .arg(
+                Arg::new("by_dir")
+                    .long("by-dir")
+                    .takes_value(true)
+                    .min_values(0)
+                    .max_values(1)
+                    .default_missing_value("1")
+                    .value_name("DEPTH")
+                    .conflicts_with_all(&["output", "files", "streaming"])
+                    .help(
+                        "Groups the statistics by directory instead of by language, down to \
+                        DEPTH levels below the counted paths (default 1).",
+                    ),
+            )
+            .arg(
+                Arg::new("revision")
+                    .long("revision")
+                    .takes_value(true)
+                    .value_name("REV")
+                    .conflicts_with_all(&["cache", "file_input"])
+                    .help(
+                        "Count the files of a git revision (a commit, tag or branch) instead of \
+                        the working tree, without checking it out. Each input path must be \
+                        inside a git repository.",
+                    ),
+            )
+            .arg(
+                Arg::new("diff")
+                    .long("diff")
+                    .takes_value(true)
+                    .value_name("FROM..TO")
+                    .conflicts_with_all(&["revision", "cache", "file_input", "by_dir", "streaming"])
+                    .help(
+                        "Report the lines added and removed in each language between two git \
+                        revisions, TO being HEAD when left out. Each input path must be inside \
+                        a git repository.",
+                    ),
+            )
+            .arg(
+                Arg::new("blame")
+                    .long("blame")
+                    .conflicts_with_all(&["revision", "diff", "file_input", "by_dir", "streaming"])
+                    .help(
+                        "Split the lines of each language by the author who changed them last, \
+                        according to git blame. Lines that aren't committed yet are counted \
+                        apart, and files outside of a git repository are left out.",
+                    ),
+            )
+            .arg(
+                Arg::new("hotspots")
+                    .long("hotspots")
+                    .takes_value(true)
+                    .min_values(0)
+                    .max_values(1)
+                    .require_equals(true)
+                    .default_missing_value("commits")
+                    .possible_values(["commits", "lines"])
+                    .value_name("RANK")
+                    .conflicts_with_all(&["revision", "diff", "blame", "file_input", "streaming"])
+                    .help(
+                        "Rank files by how often they changed in the git history, by commits \
+                        (default) or by lines added and removed, next to their current size. \
+                        With --by-dir, directories are ranked instead of files.",
+                    ),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .takes_value(true)
+                    .value_name("WHEN")
+                    .requires("hotspots")
+                    .help(
+                        "Only count changes from WHEN on, either a date (2024-01-31) or a time \
+                        ago in days, weeks, months or years (90d, 12w, 6m, 1y).",
+                    ),
+            )
+            .arg(
+                Arg::new("until")
+                    .long("until")
+                    .takes_value(true)
+                    .value_name("WHEN")
+                    .requires("hotspots")
+                    .help("Only count changes from before WHEN, given like --since."),
+            )
+            .arg(
+                Arg::new("cache")
+                    .long("cache")
+                    .takes_value(true)
+                    .min_values(0)
+                    .max_values(1)
+                    .require_equals(true)
+                    .value_name("FILE")
+                    .help(
+                        "Only reparse files that changed since the last run with --cache. \
+                        The results are kept in FILE, or by default in a file in the user's \
+                        cache directory for each set of input paths.",
+                    ),
+            )
+            .arg(
+                Arg::new("cache_hash")
+                    .long("cache-hash")
+                    .requires("cache")
+                    .help(
+                        "Also compare file contents with --cache, so that files whose \
+                        modification time changed without their contents changing aren't \
+                        reparsed.",
+                    ),
+            )
             .arg(
                 Arg::new("compact")
                     .long("compact")
@@ -224,15 +387,23 @@ impl Cli {
         let no_ignore_parent = matches.is_present("no_ignore_parent");
         let no_ignore_dot = matches.is_present("no_ignore_dot");
         let no_ignore_vcs = matches.is_present("no_ignore_vcs");
+        let skip_generated = matches.is_present("skip_generated");
         let print_languages = matches.is_present("languages");
         let verbose = matches.occurrences_of("verbose");
         let compact = matches.is_present("compact");
+        let cache_hash = matches.is_present("cache_hash");
         let types = matches.value_of("types").map(|e| {
             e.split(',')
                 .map(str::parse::<LanguageType>)
                 .filter_map(Result::ok)
                 .collect()
         });
+        let categories = matches
+            .value_of("categories")
+            .map(|e| e.split(',').map(parse_or_exit::<Category>).collect());
+        let excluded_categories = matches
+            .value_of("excluded_categories")
+            .map(|e| e.split(',').map(parse_or_exit::<Category>).collect());
 
         let num_format_style: NumberFormatStyle = matches
             .value_of("num_format_style")
@@ -259,6 +430,23 @@ impl Cli {
         // is supported) but this will fail if support is not compiled in and
         // give a useful error to the user.
         let output = matches.value_of("output").map(parse_or_exit::<Format>);
+        let by_dir = matches.value_of("by_dir").map(parse_or_exit::<usize>);
+        let revision = matches.value_of("revision").map(String::from);
+        let diff = matches.value_of("diff").map(|range| {
+            let (from, to) = range.split_once("..").unwrap_or((range, ""));
+            let to = if to.is_empty() { "HEAD" } else { to };
+            (from.to_owned(), to.to_owned())
+        });
+        let blame = matches.is_present("blame");
+        let hotspots = matches
+            .value_of("hotspots")
+            .map(parse_or_exit::<HotspotRank>);
+        let since = matches
+            .value_of("since")
+            .map(|when| parse_or_exit::<Timestamp>(when).0);
+        let until = matches
+            .value_of("until")
+            .map(|when| parse_or_exit::<Timestamp>(when).0);
         let streaming = matches
             .value_of("streaming")
             .map(parse_or_exit::<Streaming>);
@@ -274,13 +462,24 @@ impl Cli {
             no_ignore_parent,
             no_ignore_dot,
             no_ignore_vcs,
+            skip_generated,
             output,
+            by_dir,
+            revision,
+            diff,
+            blame,
+            hotspots,
+            since,
+            until,
             streaming,
             print_languages,
             sort,
             sort_reverse,
             types,
+            categories,
+            excluded_categories,
             compact,
+            cache_hash,
             number_format,
             verbose,
         };
@@ -302,6 +501,31 @@ impl Cli {
         ignored_directories
     }
 
+    /// Where to keep the per-file cache, `None` unless `--cache` was given.
+    pub fn cache_file(&self) -> Option<PathBuf> {
+        if !self.matches.is_present("cache") {
+            return None;
+        }
+
+        if let Some(file) = self.matches.value_of("cache") {
+            return Some(PathBuf::from(file));
+        }
+
+        let mut hasher = DefaultHasher::new();
+        for path in self.input() {
+            fs::canonicalize(path)
+                .unwrap_or_else(|_| PathBuf::from(path))
+                .hash(&mut hasher);
+        }
+
+        let cache_dir = etcetera::choose_base_strategy().ok()?.cache_dir();
+        Some(
+            cache_dir
+                .join("tokei")
+                .join(format!("{:016x}.json", hasher.finish())),
+        )
+    }
+
     pub fn input(&self) -> Vec<&str> {
         match self.matches.values_of("input") {
             Some(vs) => vs.collect(),
@@ -374,7 +598,10 @@ impl Cli {
     /// * `no_ignore_parent`
     /// * `no_ignore_dot`
     /// * `no_ignore_vcs`
+    /// * `skip_generated`
     /// * `types`
+    /// * `categories`
+    /// * `excluded_categories`
     pub fn override_config(&mut self, mut config: Config) -> Config {
         config.hidden = if self.hidden {
             Some(true)
@@ -406,25 +633,37 @@ impl Cli {
             config.no_ignore_vcs
         };
 
+        config.skip_generated = if self.skip_generated {
+            Some(true)
+        } else {
+            config.skip_generated
+        };
+
         config.for_each_fn = match self.streaming {
-            Some(Streaming::Json) => Some(|l: LanguageType, e| {
+            Some(Streaming::Json) => Some(Arc::new(|l: LanguageType, e: Report| {
                 println!("{}", serde_json::json!({"language": l.name(), "stats": e}));
-            }),
-            Some(Streaming::Simple) => Some(|l: LanguageType, e| {
+            })),
+            Some(Streaming::Simple) => Some(Arc::new(|l: LanguageType, e: Report| {
                 println!(
-                    "{:>10} {:<80} {:>12} {:>12} {:>12} {:>12}",
+                    "{:>10} {:<80} {:>12} {:>12} {:>12} {:>12} {:>12}",
                     l.name(),
                     e.name.to_string_lossy().to_string(),
                     e.stats.lines(),
                     e.stats.code,
                     e.stats.comments,
+                    e.stats.docs,
                     e.stats.blanks
                 );
-            }),
+            })),
             _ => None,
         };
 
         config.types = mem::replace(&mut self.types, None).or(config.types);
+        config.categories = self.categories.take().or(config.categories);
+        config.excluded_categories = self
+            .excluded_categories
+            .take()
+            .or(config.excluded_categories);
 
         config
     }
@@ -4,6 +4,7 @@ use std::{
     io::{self, Write},
     process,
     str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use clap::crate_version;
@@ -11,12 +12,17 @@ use colored::Colorize;
 use num_format::ToFormattedString;
 
 use crate::input::Format;
-use tokei::{find_char_boundary, CodeStats, Language, LanguageType, Report};
+use tokei::{
+    find_char_boundary, AuthorStats, Blame, CodeStats, CodeStatsDelta, DirectoryTree, FileDiff,
+    Hotspot, Hotspots, Language, LanguageType, Report, RevisionDiff, RevisionLanguageDiff,
+};
 
-pub const FALLBACK_ROW_LEN: usize = 79;
-const NO_LANG_HEADER_ROW_LEN: usize = 67;
-const NO_LANG_ROW_LEN: usize = 61;
-const NO_LANG_ROW_LEN_NO_SPACES: usize = 54;
+pub const FALLBACK_ROW_LEN: usize = 92;
+const NO_LANG_HEADER_ROW_LEN: usize = 80;
+const NO_LANG_ROW_LEN: usize = 74;
+const NO_LANG_ROW_LEN_NO_SPACES: usize = 67;
+const NO_HOTSPOT_HEADER_ROW_LEN: usize = 67;
+const NO_HOTSPOT_ROW_LEN: usize = 61;
 const IDENT_INACCURATE: &str = "(!)";
 
 pub fn crate_version() -> String {
@@ -119,6 +125,58 @@ impl NumberFormatStyle {
     }
 }
 
+/// A point in time given on the command line, in seconds since the Unix
+/// epoch. Either a date (`2024-01-31`, at midnight UTC) or a time ago in
+/// days, weeks, months or years (`90d`, `12w`, `6m`, `1y`).
+#[derive(Debug, Copy, Clone)]
+pub struct Timestamp(pub i64);
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((i, unit)) = s.char_indices().last() {
+            let days = match unit {
+                'd' => Some(1),
+                'w' => Some(7),
+                'm' => Some(30),
+                'y' => Some(365),
+                _ => None,
+            };
+            if let (Some(days), Ok(count)) = (days, s[..i].parse::<i64>()) {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as i64);
+                return Ok(Self(now - count * days * 86_400));
+            }
+        }
+
+        let mut parts = s.splitn(3, '-').map(str::parse::<i64>);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(year)), Some(Ok(month)), Some(Ok(day)))
+                if (1..=12).contains(&month) && (1..=31).contains(&day) =>
+            {
+                Ok(Self(days_from_civil(year, month, day) * 86_400))
+            }
+            _ => Err(format!(
+                "Expected a date like '2024-01-31' or a time ago like '90d', '12w', '6m' or \
+                '1y', but got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// The days from the Unix epoch to a date of the Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
 pub struct Printer<W> {
     writer: W,
     columns: usize,
@@ -153,18 +211,319 @@ impl<W: Write> Printer<W> {
         self.print_row()?;
         writeln!(
             self.writer,
-            " {:<6$} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            " {:<7$} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
             "Language".bold().blue(),
             "Files".bold().blue(),
             "Lines".bold().blue(),
             "Code".bold().blue(),
             "Comments".bold().blue(),
+            "Docs".bold().blue(),
             "Blanks".bold().blue(),
             self.columns - NO_LANG_HEADER_ROW_LEN
         )?;
         self.print_row()
     }
 
+    /// Prints the directories of `tree` down to `depth` levels, each
+    /// directory indented under its parent, followed by the total.
+    pub fn print_directory_tree(&mut self, tree: &DirectoryTree, depth: usize) -> io::Result<()> {
+        self.print_row()?;
+        writeln!(
+            self.writer,
+            " {:<7$} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "Directory".bold().blue(),
+            "Files".bold().blue(),
+            "Lines".bold().blue(),
+            "Code".bold().blue(),
+            "Comments".bold().blue(),
+            "Docs".bold().blue(),
+            "Blanks".bold().blue(),
+            self.columns - NO_LANG_HEADER_ROW_LEN
+        )?;
+        self.print_row()?;
+
+        for (path, directory) in tree.walk(Some(depth)) {
+            let level = path.components().count();
+            let name = path
+                .file_name()
+                .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+            let name = format!("{}{}", "  ".repeat(level - 1), name);
+            self.print_directory(directory, &name)?;
+        }
+
+        self.print_row()?;
+        self.print_language_name(false, "Total", None)?;
+        write!(self.writer, " ")?;
+        writeln!(
+            self.writer,
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            tree.files.to_formatted_string(&self.number_format).blue(),
+            tree.stats
+                .lines()
+                .to_formatted_string(&self.number_format)
+                .blue(),
+            tree.stats
+                .code
+                .to_formatted_string(&self.number_format)
+                .blue(),
+            tree.stats
+                .comments
+                .to_formatted_string(&self.number_format)
+                .blue(),
+            tree.stats
+                .docs
+                .to_formatted_string(&self.number_format)
+                .blue(),
+            tree.stats
+                .blanks
+                .to_formatted_string(&self.number_format)
+                .blue(),
+        )?;
+        self.print_row()
+    }
+
+    fn print_directory(&mut self, directory: &DirectoryTree, name: &str) -> io::Result<()> {
+        self.print_language_name(false, name, None)?;
+        write!(self.writer, " ")?;
+        writeln!(
+            self.writer,
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            directory.files.to_formatted_string(&self.number_format),
+            directory
+                .stats
+                .lines()
+                .to_formatted_string(&self.number_format),
+            directory
+                .stats
+                .code
+                .to_formatted_string(&self.number_format),
+            directory
+                .stats
+                .comments
+                .to_formatted_string(&self.number_format),
+            directory
+                .stats
+                .docs
+                .to_formatted_string(&self.number_format),
+            directory
+                .stats
+                .blanks
+                .to_formatted_string(&self.number_format),
+        )
+    }
+
+    /// Prints the authors of each language with the lines they changed last,
+    /// followed by the authors of every language together.
+    pub fn print_blame(&mut self, blame: &Blame) -> io::Result<()> {
+        self.print_header()?;
+
+        for (i, (language, authors)) in blame.languages.iter().enumerate() {
+            if i > 0 {
+                self.print_subrow()?;
+            }
+            self.print_language_name(false, &language.to_string(), None)?;
+            writeln!(self.writer)?;
+            for author in authors {
+                self.print_author(author)?;
+            }
+        }
+
+        self.print_row()?;
+        self.print_language_name(false, "Total", None)?;
+        writeln!(self.writer)?;
+        for author in blame.authors() {
+            self.print_author(&author)?;
+        }
+        self.print_row()
+    }
+
+    fn print_author(&mut self, author: &AuthorStats) -> io::Result<()> {
+        let name = if author.email.is_empty() {
+            author.name.clone()
+        } else {
+            format!("{} <{}>", author.name, author.email)
+        };
+        self.print_language_name(false, &name, Some(" |-"))?;
+        write!(self.writer, " ")?;
+        writeln!(
+            self.writer,
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            author.files.to_formatted_string(&self.number_format),
+            author
+                .stats
+                .lines()
+                .to_formatted_string(&self.number_format),
+            author.stats.code.to_formatted_string(&self.number_format),
+            author
+                .stats
+                .comments
+                .to_formatted_string(&self.number_format),
+            author.stats.docs.to_formatted_string(&self.number_format),
+            author.stats.blanks.to_formatted_string(&self.number_format),
+        )
+    }
+
+    /// Prints the files ranked by how often they changed, or the directories
+    /// down to `depth` levels when given, next to their current size.
+    pub fn print_hotspots(&mut self, hotspots: &Hotspots, depth: Option<usize>) -> io::Result<()> {
+        self.print_row()?;
+        writeln!(
+            self.writer,
+            " {:<6$} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            if depth.is_some() { "Directory" } else { "File" }
+                .bold()
+                .blue(),
+            "Commits".bold().blue(),
+            "Added".bold().blue(),
+            "Removed".bold().blue(),
+            "Code".bold().blue(),
+            "Lines".bold().blue(),
+            self.columns - NO_HOTSPOT_HEADER_ROW_LEN
+        )?;
+        self.print_row()?;
+
+        match depth {
+            Some(depth) => {
+                for directory in &hotspots.directories {
+                    if directory.name.components().count() <= depth {
+                        self.print_hotspot(directory)?;
+                    }
+                }
+            }
+            None => {
+                for file in &hotspots.files {
+                    self.print_hotspot(file)?;
+                }
+            }
+        }
+
+        self.print_row()
+    }
+
+    fn print_hotspot(&mut self, hotspot: &Hotspot) -> io::Result<()> {
+        let name = hotspot.name.to_string_lossy();
+        let max_len = self.columns - NO_HOTSPOT_ROW_LEN;
+        let name = if name.len() > max_len {
+            // Add 1 to the index to account for the '|' we add to the output string
+            let from = find_char_boundary(&name, name.len() + 1 - max_len);
+            Cow::Owned(format!("|{}", &name[from..]))
+        } else {
+            name
+        };
+
+        writeln!(
+            self.writer,
+            " {: <max$} {:>6} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            hotspot
+                .churn
+                .commits
+                .to_formatted_string(&self.number_format),
+            hotspot.churn.added.to_formatted_string(&self.number_format),
+            hotspot
+                .churn
+                .removed
+                .to_formatted_string(&self.number_format),
+            hotspot.stats.code.to_formatted_string(&self.number_format),
+            hotspot
+                .stats
+                .lines()
+                .to_formatted_string(&self.number_format),
+            max = max_len
+        )
+    }
+
+    /// Prints the lines added and removed in each language between two
+    /// revisions as `+added/-removed`, followed by the total. Changed files
+    /// are listed with their net change when printing files.
+    pub fn print_revision_diff(&mut self, diff: &RevisionDiff) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            " Changes from {} to {}",
+            diff.from.bold(),
+            diff.to.bold()
+        )?;
+        self.print_header()?;
+
+        for (language, changes) in &diff.languages {
+            self.print_language_changes(&language.to_string(), changes, changes.files.len())?;
+
+            if self.list_files {
+                self.print_subrow()?;
+                for file in &changes.files {
+                    self.print_file_diff(file)?;
+                }
+                self.print_subrow()?;
+            }
+        }
+
+        let total = diff.total();
+        let files = diff.languages.values().map(|l| l.files.len()).sum();
+        self.print_row()?;
+        self.print_language_changes("Total", &total, files)?;
+        self.print_row()
+    }
+
+    fn print_language_changes(
+        &mut self,
+        name: &str,
+        changes: &RevisionLanguageDiff,
+        files: usize,
+    ) -> io::Result<()> {
+        let (added, removed) = (&changes.added, &changes.removed);
+        self.print_language_name(changes.inaccurate, name, None)?;
+        write!(self.writer, " ")?;
+        writeln!(
+            self.writer,
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            files.to_formatted_string(&self.number_format),
+            self.format_changes(added.lines(), removed.lines()),
+            self.format_changes(added.code, removed.code),
+            self.format_changes(added.comments, removed.comments),
+            self.format_changes(added.docs, removed.docs),
+            self.format_changes(added.blanks, removed.blanks),
+        )
+    }
+
+    fn print_file_diff(&mut self, file: &FileDiff) -> io::Result<()> {
+        let name = file.name.to_string_lossy();
+        let max_len = self.path_length - 8;
+        let name = if name.len() > max_len {
+            // Add 1 to the index to account for the '|' we add to the output string
+            let from = find_char_boundary(&name, name.len() + 1 - max_len);
+            Cow::Owned(format!("|{}", &name[from..]))
+        } else {
+            name
+        };
+        let stats: &CodeStatsDelta = &file.stats;
+
+        writeln!(
+            self.writer,
+            " {: <max$} {:>7} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            file.change.name(),
+            self.format_delta(stats.lines()),
+            self.format_delta(stats.code),
+            self.format_delta(stats.comments),
+            self.format_delta(stats.docs),
+            self.format_delta(stats.blanks),
+            max = max_len
+        )
+    }
+
+    fn format_changes(&self, added: usize, removed: usize) -> String {
+        format!(
+            "+{}/-{}",
+            added.to_formatted_string(&self.number_format),
+            removed.to_formatted_string(&self.number_format)
+        )
+    }
+
+    fn format_delta(&self, delta: isize) -> String {
+        let sign = if delta > 0 { "+" } else { "" };
+        format!("{}{}", sign, delta.to_formatted_string(&self.number_format))
+    }
+
     pub fn print_inaccuracy_warning(&mut self) -> io::Result<()> {
         writeln!(
             self.writer,
@@ -181,7 +540,7 @@ impl<W: Write> Printer<W> {
         write!(self.writer, " ")?;
         writeln!(
             self.writer,
-            "{:>6} {:>12} {:>12} {:>12} {:>12}",
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
             language
                 .reports
                 .len()
@@ -189,6 +548,7 @@ impl<W: Write> Printer<W> {
             language.lines().to_formatted_string(&self.number_format),
             language.code.to_formatted_string(&self.number_format),
             language.comments.to_formatted_string(&self.number_format),
+            language.docs.to_formatted_string(&self.number_format),
             language.blanks.to_formatted_string(&self.number_format),
         )
     }
@@ -201,7 +561,7 @@ impl<W: Write> Printer<W> {
         write!(self.writer, " ")?;
         writeln!(
             self.writer,
-            "{:>6} {:>12} {:>12} {:>12} {:>12}",
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
             language
                 .children
                 .values()
@@ -221,6 +581,10 @@ impl<W: Write> Printer<W> {
                 .comments
                 .to_formatted_string(&self.number_format)
                 .blue(),
+            language
+                .docs
+                .to_formatted_string(&self.number_format)
+                .blue(),
             language
                 .blanks
                 .to_formatted_string(&self.number_format)
@@ -269,11 +633,13 @@ impl<W: Write> Printer<W> {
         self.print_language_name(false, &language_type.to_string(), Some(" |-"))?;
         let mut code = 0;
         let mut comments = 0;
+        let mut docs = 0;
         let mut blanks = 0;
 
         for stats in stats.iter().map(tokei::CodeStats::summarise) {
             code += stats.code;
             comments += stats.comments;
+            docs += stats.docs;
             blanks += stats.blanks;
         }
 
@@ -282,11 +648,12 @@ impl<W: Write> Printer<W> {
         } else {
             writeln!(
                 self.writer,
-                " {:>6} {:>12} {:>12} {:>12} {:>12}",
+                " {:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
                 stats.len().to_formatted_string(&self.number_format),
-                (code + comments + blanks).to_formatted_string(&self.number_format),
+                (code + comments + docs + blanks).to_formatted_string(&self.number_format),
                 code.to_formatted_string(&self.number_format),
                 comments.to_formatted_string(&self.number_format),
+                docs.to_formatted_string(&self.number_format),
                 blanks.to_formatted_string(&self.number_format),
             )
         }
@@ -306,6 +673,7 @@ impl<W: Write> Printer<W> {
         let summary = parent.summarise();
         subtotal.stats.code += summary.code;
         subtotal.stats.comments += summary.comments;
+        subtotal.stats.docs += summary.docs;
         subtotal.stats.blanks += summary.blanks;
         self.print_report_with_name(&subtotal)?;
 
@@ -406,11 +774,12 @@ impl<W: Write> Printer<W> {
 
         writeln!(
             self.writer,
-            " {:>6} {:>12} {:>12} {:>12} {:>12}",
+            " {:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
             " ",
             stats.lines().to_formatted_string(&self.number_format),
             stats.code.to_formatted_string(&self.number_format),
             stats.comments.to_formatted_string(&self.number_format),
+            stats.docs.to_formatted_string(&self.number_format),
             stats.blanks.to_formatted_string(&self.number_format),
         )
     }
@@ -423,6 +792,7 @@ impl<W: Write> Printer<W> {
         let mut subtotal = tokei::Report::new("|- (Total)".into());
         subtotal.stats.code += report.stats.code;
         subtotal.stats.comments += report.stats.comments;
+        subtotal.stats.docs += report.stats.docs;
         subtotal.stats.blanks += report.stats.blanks;
 
         for (language_type, stats) in &report.stats.blobs {
@@ -458,7 +828,7 @@ impl<W: Write> Printer<W> {
     ) -> io::Result<()> {
         writeln!(
             self.writer,
-            " {: <max$} {:>12} {:>12} {:>12} {:>12}",
+            " {: <max$} {:>12} {:>12} {:>12} {:>12} {:>12}",
             name,
             report
                 .stats
@@ -469,6 +839,7 @@ impl<W: Write> Printer<W> {
                 .stats
                 .comments
                 .to_formatted_string(&self.number_format),
+            report.stats.docs.to_formatted_string(&self.number_format),
             report.stats.blanks.to_formatted_string(&self.number_format),
             max = max_len
         )
@@ -1,10 +1,31 @@
-use std::{env, fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use dashmap::DashMap;
 use etcetera::BaseStrategy;
+use globset::{GlobBuilder, GlobMatcher};
+use once_cell::sync::Lazy;
 
-use crate::language::LanguageType;
+use crate::cache::ReportCache;
+use crate::cancel::CancellationToken;
+use crate::language::{
+    language_type::is_hex_color, Category, CustomLanguage, LanguageDefinition, LanguageType,
+};
 use crate::sort::Sort;
 use crate::stats::Report;
+use crate::utils::fs as fsutils;
+
+/// A callback run for every file once it has been parsed, see
+/// [`Config::for_each_fn`].
+pub type ForEachFn = Arc<dyn Fn(LanguageType, Report) + Send + Sync>;
+
+/// A callback run for every file once it has been found and matched to a
+/// language, see [`Config::for_each_discovered_fn`].
+pub type ForEachDiscoveredFn = Arc<dyn Fn(LanguageType, &Path) + Send + Sync>;
 
 /// A configuration struct for how [`Languages::get_statistics`] searches and
 /// counts languages.
@@ -19,7 +40,7 @@ use crate::stats::Report;
 /// ```
 ///
 /// [`Languages::get_statistics`]: struct.Languages.html#method.get_statistics
-#[derive(Debug, Default, Deserialize)]
+#[derive(Default, Deserialize)]
 pub struct Config {
     /// Width of columns to be printed to the terminal. _This option is ignored
     /// in the library._ *Default:* Auto detected width of the terminal.
@@ -38,8 +59,13 @@ pub struct Config {
     /// Don't respect VCS ignore files (.gitignore, .hgignore, etc.), including those in
     /// parent directories. *Default:* `false`.
     pub no_ignore_vcs: Option<bool>,
-    /// Whether to treat doc strings in languages as comments.  *Default:*
-    /// `false`.
+    /// Don't count generated and vendored files at all, rather than keeping
+    /// them in [`Language::generated`](crate::Language::generated).
+    /// *Default:* `false`.
+    pub skip_generated: Option<bool>,
+    /// Whether to treat doc strings in languages as documentation comments,
+    /// counted in [`CodeStats::docs`](crate::CodeStats::docs), rather than
+    /// code. *Default:* `false`.
     pub treat_doc_strings_as_comments: Option<bool>,
     /// Sort languages. *Default:* `None`.
     pub sort: Option<Sort>,
@@ -47,16 +73,135 @@ pub struct Config {
     /// containing `C`, `Cpp`, and `Rust` with a `Config.types` of `[Cpp, Rust]`
     /// will count only `Cpp` and `Rust`. *Default:* `None`.
     pub types: Option<Vec<LanguageType>>,
-    // /// A map of individual language configuration.
-    // pub languages: Option<HashMap<LanguageType, LanguageConfig>>,
+    /// Filters languages searched to just those of the categories provided,
+    /// see [`LanguageType::category`]. *Default:* `None`.
+    pub categories: Option<Vec<Category>>,
+    /// Leaves out the languages of the categories provided, e.g. `[Data]` to
+    /// count everything but configuration and data files. *Default:* `None`.
+    pub excluded_categories: Option<Vec<Category>>,
+    /// Languages defined at runtime by their name, counted as
+    /// [`LanguageType::Custom`]. They take precedence over the built in
+    /// languages for the extensions and file names they list.
+    /// *Default:* `None`.
+    pub custom_languages: Option<BTreeMap<String, LanguageDefinition>>,
+    /// A map of individual language configuration, see [`LanguageConfig`].
+    /// *Default:* `None`.
+    pub languages: Option<BTreeMap<LanguageType, LanguageConfig>>,
     /// Whether to output only the paths for downstream batch processing
     /// *Default:* false
     #[serde(skip)]
     /// adds a closure for each function, e.g., print the result
-    pub for_each_fn: Option<fn(LanguageType, Report)>,
+    pub for_each_fn: Option<ForEachFn>,
+    #[serde(skip)]
+    /// adds a closure for each file found by the walker, before it is parsed.
+    /// Together with `for_each_fn` this can be used to track the progress of
+    /// a scan.
+    pub for_each_discovered_fn: Option<ForEachDiscoveredFn>,
+    #[serde(skip)]
+    /// Stops the search early once cancelled. *Default:* `None`.
+    pub cancellation: Option<CancellationToken>,
+    #[serde(skip)]
+    /// Reuses the statistics of files unchanged since a previous search.
+    /// *Default:* `None`.
+    pub cache: Option<Arc<ReportCache>>,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("columns", &self.columns)
+            .field("hidden", &self.hidden)
+            .field("no_ignore", &self.no_ignore)
+            .field("no_ignore_parent", &self.no_ignore_parent)
+            .field("no_ignore_dot", &self.no_ignore_dot)
+            .field("no_ignore_vcs", &self.no_ignore_vcs)
+            .field("skip_generated", &self.skip_generated)
+            .field(
+                "treat_doc_strings_as_comments",
+                &self.treat_doc_strings_as_comments,
+            )
+            .field("sort", &self.sort)
+            .field("types", &self.types)
+            .field("categories", &self.categories)
+            .field("excluded_categories", &self.excluded_categories)
+            .field("languages", &self.languages)
+            .field("custom_languages", &self.custom_languages)
+            .field("for_each_fn", &self.for_each_fn.as_ref().map(|_| ".."))
+            .field(
+                "for_each_discovered_fn",
+                &self.for_each_discovered_fn.as_ref().map(|_| ".."),
+            )
+            .field("cancellation", &self.cancellation)
+            .field("cache", &self.cache.as_ref().map(|c| c.len()))
+            .finish()
+    }
 }
 
 impl Config {
+    /// Whether the search using this config has been cancelled, in which
+    /// case the caller skips what it was about to do.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::stop_if_cancelled)
+    }
+
+    /// Whether the search using this config left files out because it was
+    /// cancelled.
+    pub(crate) fn stopped_early(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::stopped_early)
+    }
+
+    /// Registers every language of [`Config::custom_languages`], so that
+    /// results naming them, such as a [`ReportCache`] or the output of
+    /// `tokei -o json`, can be read back.
+    pub fn register_custom_languages(&self) {
+        for (name, definition) in self.custom_languages.iter().flatten() {
+            CustomLanguage::register(name, definition);
+        }
+    }
+
+    /// Whether files of `language` are counted, going by [`Config::types`],
+    /// [`Config::categories`] and [`Config::excluded_categories`].
+    pub(crate) fn is_counted(&self, language: LanguageType) -> bool {
+        let category = language.category();
+        self.types
+            .as_deref()
+            .is_none_or(|types| types.contains(&language))
+            && self
+                .categories
+                .as_deref()
+                .is_none_or(|categories| categories.contains(&category))
+            && !self
+                .excluded_categories
+                .as_deref()
+                .is_some_and(|categories| categories.contains(&category))
+    }
+
+    /// The language `entry` is remapped to by [`Config::languages`], if any.
+    pub(crate) fn language_override(&self, entry: &Path) -> Option<LanguageType> {
+        let filename = fsutils::get_filename(entry)?;
+        let extension = fsutils::get_extension(entry);
+
+        self.languages
+            .as_ref()?
+            .iter()
+            .find(|(_, config)| config.matches(&filename, extension.as_deref()))
+            .map(|(language, _)| *language)
+    }
+
+    /// The color of `language`, its [`LanguageConfig::color`] if one is set
+    /// as `#RRGGBB` and [`LanguageType::color`] otherwise.
+    pub fn color(&self, language: LanguageType) -> &str {
+        self.languages
+            .as_ref()
+            .and_then(|languages| languages.get(&language)?.color.as_deref())
+            .filter(|color| is_hex_color(color))
+            .unwrap_or_else(|| language.color())
+    }
+
     /// Constructs a new `Config` from either `$base/tokei.toml` or
     /// `$base/.tokeirc`. `tokei.toml` takes precedence over `.tokeirc`
     /// as the latter is a hidden file on Unix and not an idiomatic
@@ -86,10 +231,21 @@ impl Config {
     /// ```toml
     /// columns = 80
     /// types = ["Python"]
+    /// excluded_categories = ["data"]
     /// treat_doc_strings_as_comments = true
-    // ///
-    // /// [[languages.Python]]
-    // /// extensions = ["py3"]
+    /// skip_generated = true
+    ///
+    /// [custom_languages.Flow]
+    /// extensions = ["flow"]
+    /// line_comment = ["--"]
+    /// quotes = [["\"", "\""]]
+    ///
+    /// [languages.Python]
+    /// extensions = ["py3"]
+    /// color = "#ffd43b"
+    ///
+    /// [languages.Groovy]
+    /// filenames = ["Jenkinsfile.*"]
     /// ```
     pub fn from_config_files() -> Self {
         let conf_dir = etcetera::choose_base_strategy()
@@ -113,12 +269,23 @@ impl Config {
             columns: current_dir
                 .columns
                 .or(home_dir.columns.or(conf_dir.columns)),
-            //languages: current_dir.languages.or(conf_dir.languages),
+            languages: current_dir
+                .languages
+                .or(home_dir.languages.or(conf_dir.languages)),
             treat_doc_strings_as_comments: current_dir.treat_doc_strings_as_comments.or(home_dir
                 .treat_doc_strings_as_comments
                 .or(conf_dir.treat_doc_strings_as_comments)),
             sort: current_dir.sort.or(home_dir.sort.or(conf_dir.sort)),
             types: current_dir.types.or(home_dir.types.or(conf_dir.types)),
+            categories: current_dir
+                .categories
+                .or(home_dir.categories.or(conf_dir.categories)),
+            excluded_categories: current_dir.excluded_categories.or(home_dir
+                .excluded_categories
+                .or(conf_dir.excluded_categories)),
+            custom_languages: current_dir
+                .custom_languages
+                .or(home_dir.custom_languages.or(conf_dir.custom_languages)),
             for_each_fn: current_dir
                 .for_each_fn
                 .or(home_dir.for_each_fn.or(conf_dir.for_each_fn)),
@@ -134,25 +301,30 @@ impl Config {
             no_ignore_vcs: current_dir
                 .no_ignore_vcs
                 .or(home_dir.no_ignore_vcs.or(conf_dir.no_ignore_vcs)),
+            skip_generated: current_dir
+                .skip_generated
+                .or(home_dir.skip_generated.or(conf_dir.skip_generated)),
             ..Self::default()
         }
     }
 }
 
-/*
-/// Configuration for a individual [`LanguageType`].
+/// Configuration for a individual [`LanguageType`], remapping which files
+/// are counted as it. Files matching a language's configuration are counted
+/// as that language ahead of tokei's own extensions and file names.
 ///
 /// ```
-/// use std::collections::HashMap;
+/// use std::collections::BTreeMap;
 /// use tokei::{Config, LanguageConfig, LanguageType};
 ///
 /// let config = Config {
 ///     languages: {
 ///         let cpp_conf = LanguageConfig {
-///             extensions: vec![String::from("c")],
+///             extensions: vec![String::from("h")],
+///             ..LanguageConfig::default()
 ///         };
 ///
-///         let mut languages_config = HashMap::new();
+///         let mut languages_config = BTreeMap::new();
 ///         languages_config.insert(LanguageType::Cpp, cpp_conf);
 ///
 ///         Some(languages_config)
@@ -161,14 +333,21 @@ impl Config {
 ///     ..Config::default()
 /// };
 ///
+/// assert_eq!(LanguageType::from_path("lib.h", &config), Some(LanguageType::Cpp));
 /// ```
 ///
 /// [`LanguageType`]: enum.LanguageType.html
-#[derive(Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
 pub struct LanguageConfig {
-    /// Additional extensions for a language. Any extensions that overlap with
-    /// already defined extensions from `tokei` will be ignored.
+    /// Extensions counted as the language, without the leading dot.
     pub extensions: Vec<String>,
+    /// File name patterns counted as the language, such as `Jenkinsfile.*`.
+    /// Patterns are globs matched against the file name regardless of case.
+    pub filenames: Vec<String>,
+    /// The color the language is shown in as `#RRGGBB`, in place of
+    /// [`LanguageType::color`]. Colors written otherwise are ignored.
+    pub color: Option<String>,
 }
 
 impl LanguageConfig {
@@ -178,11 +357,129 @@ impl LanguageConfig {
         Self::default()
     }
 
-    /// Accepts a `Vec<String>` representing additional extensions for a
-    /// language. Any extensions that overlap with already defined extensions
-    /// from `tokei` will be ignored.
+    /// Accepts a `Vec<String>` representing the extensions counted as the
+    /// language.
     pub fn extensions(&mut self, extensions: Vec<String>) {
         self.extensions = extensions;
     }
+
+    /// Accepts a `Vec<String>` of file name patterns counted as the language.
+    pub fn filenames(&mut self, filenames: Vec<String>) {
+        self.filenames = filenames;
+    }
+
+    /// Accepts the color the language is shown in, as `#RRGGBB`.
+    pub fn color(&mut self, color: String) {
+        self.color = Some(color);
+    }
+
+    /// Whether `filename` or `extension`, both lowercased, are counted as the
+    /// language. Invalid patterns never match.
+    fn matches(&self, filename: &str, extension: Option<&str>) -> bool {
+        static GLOBS: Lazy<DashMap<String, Option<GlobMatcher>>> = Lazy::new(DashMap::new);
+
+        let matches_pattern = |pattern: &String| {
+            GLOBS
+                .entry(pattern.clone())
+                .or_insert_with(|| {
+                    GlobBuilder::new(pattern)
+                        .case_insensitive(true)
+                        .literal_separator(true)
+                        .build()
+                        .map(|glob| glob.compile_matcher())
+                        .ok()
+                })
+                .as_ref()
+                .is_some_and(|glob| glob.is_match(filename))
+        };
+
+        self.filenames.iter().any(matches_pattern)
+            || extension.is_some_and(|extension| {
+                self.extensions
+                    .iter()
+                    .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(extension))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_filter_counted_languages() {
+        let config: Config = toml::from_str(
+            r#"
+            categories = ["programming", "data"]
+            excluded_categories = ["data"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.is_counted(LanguageType::Rust));
+        assert!(!config.is_counted(LanguageType::Json));
+        assert!(!config.is_counted(LanguageType::Markdown));
+        assert!(Config::default().is_counted(LanguageType::Markdown));
+    }
+
+    #[test]
+    fn language_overrides_take_precedence() {
+        let config: Config = toml::from_str(
+            r#"
+            [languages.Cpp]
+            extensions = ["h"]
+
+            [languages.Php]
+            extensions = [".inc"]
+
+            [languages.Groovy]
+            filenames = ["Jenkinsfile.*"]
+            "#,
+        )
+        .unwrap();
+
+        let language = |path: &str| LanguageType::from_path(path, &config);
+
+        assert_eq!(language("include/lib.h"), Some(LanguageType::Cpp));
+        assert_eq!(language("views/header.INC"), Some(LanguageType::Php));
+        assert_eq!(
+            language("ci/jenkinsfile.release"),
+            Some(LanguageType::Groovy)
+        );
+        assert_eq!(language("src/lib.c"), Some(LanguageType::C));
+    }
+
+    #[test]
+    fn configured_colors_override_the_defaults() {
+        let config: Config = toml::from_str(
+            r##"
+            [languages.Rust]
+            color = "#b7410e"
+
+            [languages.Python]
+            extensions = ["py3"]
+
+            [languages.Go]
+            color = "blue"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.color(LanguageType::Rust), "#b7410e");
+        assert_eq!(
+            config.color(LanguageType::Python),
+            LanguageType::Python.color()
+        );
+        assert_eq!(config.color(LanguageType::Go), "#00ADD8");
+    }
+
+    #[test]
+    fn invalid_patterns_never_match() {
+        let config = LanguageConfig {
+            filenames: vec!["[build".into()],
+            ..LanguageConfig::default()
+        };
+
+        assert!(!config.matches("[build", None));
+    }
 }
-*/
@@ -0,0 +1,545 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops,
+    path::{Path, PathBuf},
+};
+
+use crate::{utils, CodeStats, Config, Language, LanguageType, Languages};
+
+/// The signed difference between two [`CodeStats`], as `newer - older`.
+///
+/// ```
+/// # use tokei::*;
+/// let mut older = CodeStats::new();
+/// older.code = 10;
+/// let mut newer = CodeStats::new();
+/// newer.code = 4;
+/// newer.comments = 2;
+///
+/// let delta = &newer - &older;
+/// assert_eq!(-6, delta.code);
+/// assert_eq!(-4, delta.lines());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CodeStatsDelta {
+    /// The change in blank lines.
+    pub blanks: isize,
+    /// The change in lines of code.
+    pub code: isize,
+    /// The change in lines of comments.
+    pub comments: isize,
+    /// The change in lines of documentation comments.
+    #[serde(default)]
+    pub docs: isize,
+}
+
+impl CodeStatsDelta {
+    /// The change in total lines.
+    #[must_use]
+    pub fn lines(&self) -> isize {
+        self.blanks + self.code + self.comments + self.docs
+    }
+
+    /// Whether none of the counts changed.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ops::AddAssign for CodeStatsDelta {
+    fn add_assign(&mut self, rhs: Self) {
+        self.blanks += rhs.blanks;
+        self.code += rhs.code;
+        self.comments += rhs.comments;
+        self.docs += rhs.docs;
+    }
+}
+
+fn signed_delta(newer: usize, older: usize) -> isize {
+    newer as isize - older as isize
+}
+
+/// Only the top level counts are compared, call [`CodeStats::summarise`] on
+/// both sides first to include the child blobs.
+impl ops::Sub<&'_ CodeStats> for &'_ CodeStats {
+    type Output = CodeStatsDelta;
+
+    fn sub(self, rhs: &CodeStats) -> CodeStatsDelta {
+        CodeStatsDelta {
+            blanks: signed_delta(self.blanks, rhs.blanks),
+            code: signed_delta(self.code, rhs.code),
+            comments: signed_delta(self.comments, rhs.comments),
+            docs: signed_delta(self.docs, rhs.docs),
+        }
+    }
+}
+
+impl ops::Sub for CodeStats {
+    type Output = CodeStatsDelta;
+
+    fn sub(self, rhs: Self) -> CodeStatsDelta {
+        &self - &rhs
+    }
+}
+
+/// How a language changed between two results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageChange {
+    /// Only present in the newer result.
+    Added,
+    /// Only present in the older result.
+    Removed,
+    /// Present in both with different statistics.
+    Changed,
+    /// Present in both with the same statistics.
+    Unchanged,
+}
+
+impl LanguageChange {
+    /// A lowercase name for the change.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            LanguageChange::Added => "added",
+            LanguageChange::Removed => "removed",
+            LanguageChange::Changed => "changed",
+            LanguageChange::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// The difference between two results for a single language, as `newer -
+/// older`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LanguageDiff {
+    /// How the language changed as a whole.
+    pub change: LanguageChange,
+    /// The change in line counts, including child languages.
+    pub stats: CodeStatsDelta,
+    /// The change in the number of files.
+    pub files: isize,
+    /// Files only present in the newer result.
+    pub added_files: Vec<PathBuf>,
+    /// Files only present in the older result.
+    pub removed_files: Vec<PathBuf>,
+    /// Files present in both results with different statistics.
+    pub changed_files: Vec<PathBuf>,
+}
+
+impl LanguageDiff {
+    /// Compares two results that only kept their totals and file counts,
+    /// `None` meaning the language wasn't found. The file lists are left
+    /// empty.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut stats = CodeStats::new();
+    /// stats.code = 12;
+    ///
+    /// let diff = LanguageDiff::from_totals(None, Some((&stats, 2)));
+    /// assert_eq!(LanguageChange::Added, diff.change);
+    /// assert_eq!(12, diff.stats.code);
+    /// assert_eq!(2, diff.files);
+    /// ```
+    #[must_use]
+    pub fn from_totals(
+        older: Option<(&CodeStats, usize)>,
+        newer: Option<(&CodeStats, usize)>,
+    ) -> Self {
+        let empty = CodeStats::new();
+        let (older_stats, older_files) = older.unwrap_or((&empty, 0));
+        let (newer_stats, newer_files) = newer.unwrap_or((&empty, 0));
+        let stats = newer_stats - older_stats;
+        let files = signed_delta(newer_files, older_files);
+
+        let existed = older_files > 0 || older_stats.lines() > 0;
+        let exists = newer_files > 0 || newer_stats.lines() > 0;
+        let change = match (existed, exists) {
+            (false, true) => LanguageChange::Added,
+            (true, false) => LanguageChange::Removed,
+            _ if stats.is_zero() && files == 0 => LanguageChange::Unchanged,
+            _ => LanguageChange::Changed,
+        };
+
+        Self {
+            change,
+            stats,
+            files,
+            added_files: Vec::new(),
+            removed_files: Vec::new(),
+            changed_files: Vec::new(),
+        }
+    }
+}
+
+fn summary_stats(language: &Language) -> CodeStats {
+    let summary = language.summarise();
+    let mut stats = CodeStats::new();
+    stats.blanks = summary.blanks;
+    stats.code = summary.code;
+    stats.comments = summary.comments;
+    stats.docs = summary.docs;
+    stats
+}
+
+/// Compares two counts of the same language, matching their reports by file
+/// name.
+impl ops::Sub<&'_ Language> for &'_ Language {
+    type Output = LanguageDiff;
+
+    fn sub(self, rhs: &Language) -> LanguageDiff {
+        let older = rhs
+            .reports
+            .iter()
+            .map(|r| (&r.name, &r.stats))
+            .collect::<BTreeMap<_, _>>();
+        let newer = self
+            .reports
+            .iter()
+            .map(|r| (&r.name, &r.stats))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut diff = LanguageDiff::from_totals(
+            Some((&summary_stats(rhs), rhs.reports.len())),
+            Some((&summary_stats(self), self.reports.len())),
+        );
+
+        for (name, stats) in &newer {
+            match older.get(name) {
+                None => diff.added_files.push((*name).clone()),
+                Some(old) if old != stats => diff.changed_files.push((*name).clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed_files = older
+            .keys()
+            .filter(|name| !newer.contains_key(*name))
+            .map(|name| (*name).clone())
+            .collect();
+
+        if diff.change == LanguageChange::Unchanged
+            && !(diff.added_files.is_empty()
+                && diff.removed_files.is_empty()
+                && diff.changed_files.is_empty())
+        {
+            diff.change = LanguageChange::Changed;
+        }
+
+        diff
+    }
+}
+
+impl Languages {
+    /// Compares every language found in either `self` or `older`, as `self -
+    /// older`.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let older = Languages::new();
+    /// let mut newer = Languages::new();
+    /// let mut rust = Language::new();
+    /// rust.code = 5;
+    /// newer.insert(LanguageType::Rust, rust);
+    ///
+    /// let diff = newer.diff(&older);
+    /// assert_eq!(LanguageChange::Added, diff[&LanguageType::Rust].change);
+    /// ```
+    #[must_use]
+    pub fn diff(&self, older: &Languages) -> BTreeMap<LanguageType, LanguageDiff> {
+        let empty = Language::new();
+        let languages: BTreeSet<&LanguageType> = self.keys().chain(older.keys()).collect();
+        languages
+            .into_iter()
+            .map(|ty| {
+                let newer = self.get(ty).unwrap_or(&empty);
+                let older = older.get(ty).unwrap_or(&empty);
+                (*ty, newer - older)
+            })
+            .collect()
+    }
+}
+
+/// How a single file changed between two revisions.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FileDiff {
+    /// The file name, as if the newer revision was checked out.
+    pub name: PathBuf,
+    /// Whether the file was added, removed or changed. A file whose
+    /// language changed is removed from the old language and added to the
+    /// new one.
+    pub change: LanguageChange,
+    /// The change in line counts, including child languages.
+    pub stats: CodeStatsDelta,
+}
+
+/// The lines added and removed in a single language between two revisions.
+///
+/// Files are compared as a whole, so `added` holds the increase of each
+/// count in every file that grew, and `removed` the decrease in every file
+/// that shrank. A line rewritten in place, keeping its kind, counts as
+/// neither.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RevisionLanguageDiff {
+    /// Lines added, including child languages.
+    pub added: CodeStats,
+    /// Lines removed, including child languages.
+    pub removed: CodeStats,
+    /// The files that were added, removed or changed.
+    pub files: Vec<FileDiff>,
+    /// Whether some file couldn't be read.
+    pub inaccurate: bool,
+}
+
+impl RevisionLanguageDiff {
+    /// The net change in line counts.
+    #[must_use]
+    pub fn net(&self) -> CodeStatsDelta {
+        &self.added - &self.removed
+    }
+
+    fn add_file(&mut self, name: PathBuf, older: &CodeStats, newer: &CodeStats) {
+        let grown = |newer: usize, older: usize| newer.saturating_sub(older);
+        self.added.blanks += grown(newer.blanks, older.blanks);
+        self.added.code += grown(newer.code, older.code);
+        self.added.comments += grown(newer.comments, older.comments);
+        self.added.docs += grown(newer.docs, older.docs);
+        self.removed.blanks += grown(older.blanks, newer.blanks);
+        self.removed.code += grown(older.code, newer.code);
+        self.removed.comments += grown(older.comments, newer.comments);
+        self.removed.docs += grown(older.docs, newer.docs);
+
+        let change = match (older.lines(), newer.lines()) {
+            (0, 0) => LanguageChange::Changed,
+            (0, _) => LanguageChange::Added,
+            (_, 0) => LanguageChange::Removed,
+            _ => LanguageChange::Changed,
+        };
+        self.files.push(FileDiff {
+            name,
+            change,
+            stats: newer - older,
+        });
+    }
+}
+
+impl ops::AddAssign for RevisionLanguageDiff {
+    fn add_assign(&mut self, rhs: Self) {
+        self.added += rhs.added;
+        self.removed += rhs.removed;
+        self.files.extend(rhs.files);
+        self.inaccurate |= rhs.inaccurate;
+    }
+}
+
+/// The lines added and removed in each language between two revisions of a
+/// git repository, see [`RevisionDiff::between`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RevisionDiff {
+    /// The older revision.
+    pub from: String,
+    /// The newer revision.
+    pub to: String,
+    /// Every language with a changed file.
+    pub languages: BTreeMap<LanguageType, RevisionLanguageDiff>,
+}
+
+impl RevisionDiff {
+    /// Compares the files below `path`, the root or any directory of a git
+    /// repository, between the revisions `from` and `to`. Blobs are read from
+    /// the object database, and only files whose contents or language differ
+    /// are parsed. Files are selected as in
+    /// [`Languages::get_statistics_at_revision`].
+    ///
+    /// ```no_run
+    /// use tokei::{Config, RevisionDiff};
+    ///
+    /// let diff = RevisionDiff::between(".", "v12.1.2", "HEAD", &[], &Config::default()).unwrap();
+    /// println!("{} lines of code added", diff.total().added.code);
+    /// ```
+    pub fn between<A: AsRef<Path>>(
+        path: A,
+        from: &str,
+        to: &str,
+        ignored: &[&str],
+        config: &Config,
+    ) -> Result<Self, git2::Error> {
+        utils::git::diff_revisions(path.as_ref(), from, to, ignored, config)
+    }
+
+    /// The lines added and removed across every language, without the file
+    /// list.
+    #[must_use]
+    pub fn total(&self) -> RevisionLanguageDiff {
+        let mut total = RevisionLanguageDiff::default();
+        for language in self.languages.values() {
+            total.added += &language.added;
+            total.removed += &language.removed;
+            total.inaccurate |= language.inaccurate;
+        }
+        total
+    }
+
+    /// Records a file as it was in both revisions, `None` meaning it wasn't
+    /// counted in that revision. `older` and `newer` are summarised stats.
+    pub(crate) fn add_file(
+        &mut self,
+        name: PathBuf,
+        older: Option<(LanguageType, CodeStats)>,
+        newer: Option<(LanguageType, CodeStats)>,
+    ) {
+        let empty = CodeStats::new();
+        match (older, newer) {
+            (Some((old_language, old)), Some((new_language, new)))
+                if old_language == new_language =>
+            {
+                if old != new {
+                    self.languages
+                        .entry(new_language)
+                        .or_default()
+                        .add_file(name, &old, &new);
+                }
+            }
+            (older, newer) => {
+                if let Some((language, old)) = older {
+                    self.languages.entry(language).or_default().add_file(
+                        name.clone(),
+                        &old,
+                        &empty,
+                    );
+                }
+                if let Some((language, new)) = newer {
+                    self.languages
+                        .entry(language)
+                        .or_default()
+                        .add_file(name, &empty, &new);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::Report;
+
+    fn report(name: &str, code: usize) -> Report {
+        let mut report = Report::new(PathBuf::from(name));
+        report.stats.code = code;
+        report
+    }
+
+    fn language(reports: Vec<Report>) -> Language {
+        let mut language = Language::new();
+        for report in reports {
+            language.add_report(report);
+        }
+        language.total();
+        language
+    }
+
+    #[test]
+    fn language_diff_tracks_files() {
+        let older = language(vec![
+            report("a.rs", 10),
+            report("b.rs", 5),
+            report("c.rs", 1),
+        ]);
+        let newer = language(vec![
+            report("a.rs", 12),
+            report("b.rs", 5),
+            report("d.rs", 3),
+        ]);
+
+        let diff = &newer - &older;
+
+        assert_eq!(LanguageChange::Changed, diff.change);
+        assert_eq!(4, diff.stats.code);
+        assert_eq!(0, diff.files);
+        assert_eq!(vec![PathBuf::from("d.rs")], diff.added_files);
+        assert_eq!(vec![PathBuf::from("c.rs")], diff.removed_files);
+        assert_eq!(vec![PathBuf::from("a.rs")], diff.changed_files);
+    }
+
+    #[test]
+    fn moved_lines_are_a_change() {
+        let older = language(vec![report("a.rs", 10)]);
+        let newer = language(vec![report("b.rs", 10)]);
+
+        let diff = &newer - &older;
+
+        assert!(diff.stats.is_zero());
+        assert_eq!(LanguageChange::Changed, diff.change);
+    }
+
+    #[test]
+    fn languages_diff_covers_both_sides() {
+        let mut older = Languages::new();
+        older.insert(LanguageType::C, language(vec![report("main.c", 7)]));
+        older.insert(LanguageType::Rust, language(vec![report("lib.rs", 3)]));
+        let mut newer = Languages::new();
+        newer.insert(LanguageType::Rust, language(vec![report("lib.rs", 3)]));
+        newer.insert(LanguageType::Toml, language(vec![report("Cargo.toml", 2)]));
+
+        let diff = newer.diff(&older);
+
+        assert_eq!(LanguageChange::Removed, diff[&LanguageType::C].change);
+        assert_eq!(-7, diff[&LanguageType::C].stats.code);
+        assert_eq!(-1, diff[&LanguageType::C].files);
+        assert_eq!(LanguageChange::Unchanged, diff[&LanguageType::Rust].change);
+        assert_eq!(LanguageChange::Added, diff[&LanguageType::Toml].change);
+    }
+
+    fn stats(code: usize, comments: usize, blanks: usize) -> CodeStats {
+        let mut stats = CodeStats::new();
+        stats.code = code;
+        stats.comments = comments;
+        stats.blanks = blanks;
+        stats
+    }
+
+    #[test]
+    fn revision_diff_splits_added_and_removed_lines() {
+        let mut diff = RevisionDiff {
+            from: "v1".to_owned(),
+            to: "v2".to_owned(),
+            languages: BTreeMap::new(),
+        };
+        let rust = Some(LanguageType::Rust);
+        diff.add_file(
+            PathBuf::from("lib.rs"),
+            rust.map(|l| (l, stats(10, 4, 2))),
+            rust.map(|l| (l, stats(15, 1, 2))),
+        );
+        diff.add_file(
+            PathBuf::from("old.rs"),
+            rust.map(|l| (l, stats(3, 0, 1))),
+            None,
+        );
+        diff.add_file(
+            PathBuf::from("build"),
+            Some((LanguageType::Sh, stats(2, 1, 0))),
+            rust.map(|l| (l, stats(1, 0, 0))),
+        );
+
+        let rust = &diff.languages[&LanguageType::Rust];
+        assert_eq!(rust.added, stats(6, 0, 0));
+        assert_eq!(rust.removed, stats(3, 3, 1));
+        assert_eq!(rust.net().code, 3);
+        assert_eq!(
+            rust.files.iter().map(|f| f.change).collect::<Vec<_>>(),
+            [
+                LanguageChange::Changed,
+                LanguageChange::Removed,
+                LanguageChange::Added
+            ]
+        );
+        let shell = &diff.languages[&LanguageType::Sh];
+        assert_eq!(shell.files[0].change, LanguageChange::Removed);
+        assert_eq!(diff.total().removed, stats(5, 4, 1));
+    }
+}
@@ -99,12 +99,17 @@ macro_rules! supported_formats {
                     totals: languages.total()
                 };
 
+                self.print_value(&output)
+            }
+
+            /// Serializes any other output, such as a diff or a blame.
+            pub fn print_value<T: Serialize>(&self, value: &T) -> Result<String, Box<dyn Error>> {
                 match *self {
-                    Format::Json => Ok(serde_json::to_string(&output)?),
+                    Format::Json => Ok(serde_json::to_string(value)?),
                     $(
                         #[cfg(feature = $feature)] Format::$variant => {
                             let print= &{ $print_kode };
-                            Ok(print(&output)?)
+                            Ok(print(value)?)
                         }
                     ),+
                 }
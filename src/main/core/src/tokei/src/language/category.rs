@@ -0,0 +1,75 @@
+use std::{fmt, str::FromStr};
+
+use arbitrary::Arbitrary;
+
+/// The kind of a language, so that code isn't totalled together with
+/// markup, data and prose, see [`LanguageType::category`].
+///
+/// [`LanguageType::category`]: crate::LanguageType::category
+#[derive(
+    Arbitrary,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// Languages that are run or compiled, such as Rust or Shell.
+    #[default]
+    Programming,
+    /// Languages describing documents and their style, such as HTML or CSS.
+    Markup,
+    /// Configuration and serialised data, such as JSON or YAML.
+    Data,
+    /// Text written for people, such as Markdown or plain text.
+    Prose,
+}
+
+impl Category {
+    /// Every category, in the order they are shown in.
+    pub fn list() -> &'static [Self] {
+        &[
+            Category::Programming,
+            Category::Markup,
+            Category::Data,
+            Category::Prose,
+        ]
+    }
+
+    /// The lowercase name of the category, as used in `tokei.toml`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Programming => "programming",
+            Category::Markup => "markup",
+            Category::Data => "data",
+            Category::Prose => "prose",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::list()
+            .iter()
+            .copied()
+            .find(|category| category.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unsupported category: {}", s))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, path::Path, sync::RwLock};
+
+use arbitrary::Arbitrary;
+use once_cell::sync::Lazy;
+
+use super::{language_type::is_hex_color, Category, LanguageType};
+use crate::utils::fs as fsutils;
+
+/// The syntax of a language defined at runtime in the `custom_languages`
+/// table of `tokei.toml`, see [`Config::custom_languages`]. The keys mirror
+/// those of `languages.json`, and every key is optional.
+///
+/// ```
+/// use tokei::LanguageDefinition;
+///
+/// let definition: LanguageDefinition = toml::from_str(
+///     r#"
+///     extensions = ["flow"]
+///     line_comment = ["--"]
+///     multi_line_comments = [["{-", "-}"]]
+///     quotes = [["\"", "\""]]
+///     "#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(definition.line_comment, ["--"]);
+/// ```
+///
+/// [`Config::custom_languages`]: crate::Config::custom_languages
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct LanguageDefinition {
+    /// The file extensions of the language, without the leading dot.
+    pub extensions: Vec<String>,
+    /// Whole file names, such as `Flowfile`, that belong to the language.
+    pub filenames: Vec<String>,
+    /// The single line comments of the language.
+    pub line_comment: Vec<String>,
+    /// The start and end of the multi-line comments of the language.
+    pub multi_line_comments: Vec<(String, String)>,
+    /// Whether multi-line comments can be nested.
+    pub nested: bool,
+    /// The start and end of comments that can always be nested.
+    pub nested_comments: Vec<(String, String)>,
+    /// The start and end of the string literals of the language.
+    pub quotes: Vec<(String, String)>,
+    /// The start and end of string literals without escapes.
+    pub verbatim_quotes: Vec<(String, String)>,
+    /// The start and end of doc strings.
+    pub doc_quotes: Vec<(String, String)>,
+    /// The category of the language. *Default:* `programming`.
+    pub category: Category,
+    /// The color of the language as `#RRGGBB`, other colors are ignored.
+    /// *Default:* [`LanguageType::DEFAULT_COLOR`].
+    pub color: Option<String>,
+}
+
+impl LanguageDefinition {
+    fn matches(&self, filename: &str, extension: Option<&str>) -> bool {
+        self.filenames
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(filename))
+            || extension.is_some_and(|e| self.extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+    }
+}
+
+/// A language defined at runtime, the value of [`LanguageType::Custom`].
+/// Every distinct definition gets its own handle for the life of the
+/// process, so handles are only meaningful within it.
+#[derive(Arbitrary, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CustomLanguage(usize);
+
+/// A definition with its strings leaked, so that custom languages can be
+/// read through the same `&'static` accessors as the built in ones.
+#[derive(Debug)]
+pub(crate) struct CustomSyntax {
+    pub(crate) name: &'static str,
+    pub(crate) category: Category,
+    pub(crate) color: &'static str,
+    pub(crate) line_comments: &'static [&'static str],
+    pub(crate) multi_line_comments: &'static [(&'static str, &'static str)],
+    pub(crate) allows_nested: bool,
+    pub(crate) nested_comments: &'static [(&'static str, &'static str)],
+    pub(crate) quotes: &'static [(&'static str, &'static str)],
+    pub(crate) verbatim_quotes: &'static [(&'static str, &'static str)],
+    pub(crate) doc_quotes: &'static [(&'static str, &'static str)],
+    pub(crate) any_multi_line_comments: &'static [(&'static str, &'static str)],
+    pub(crate) any_comments: &'static [&'static str],
+    pub(crate) important_syntax: &'static [&'static str],
+}
+
+static UNKNOWN: CustomSyntax = CustomSyntax {
+    name: "Unknown",
+    category: Category::Programming,
+    color: LanguageType::DEFAULT_COLOR,
+    line_comments: &[],
+    multi_line_comments: &[],
+    allows_nested: false,
+    nested_comments: &[],
+    quotes: &[],
+    verbatim_quotes: &[],
+    doc_quotes: &[],
+    any_multi_line_comments: &[],
+    any_comments: &[],
+    important_syntax: &[],
+};
+
+type Registry = Vec<(String, LanguageDefinition, &'static CustomSyntax)>;
+
+static REGISTRY: Lazy<RwLock<Registry>> = Lazy::new(RwLock::default);
+
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_owned().into_boxed_str())
+}
+
+fn leak_slice<T>(items: Vec<T>) -> &'static [T] {
+    Box::leak(items.into_boxed_slice())
+}
+
+fn leak_pairs(pairs: &[(String, String)]) -> Vec<(&'static str, &'static str)> {
+    pairs.iter().map(|(s, e)| (leak(s), leak(e))).collect()
+}
+
+impl CustomSyntax {
+    fn new(name: &str, definition: &LanguageDefinition) -> Self {
+        let multi_line_comments = leak_pairs(&definition.multi_line_comments);
+        let nested_comments = leak_pairs(&definition.nested_comments);
+        let quotes = leak_pairs(&definition.quotes);
+        let doc_quotes = leak_pairs(&definition.doc_quotes);
+        let line_comments = definition
+            .line_comment
+            .iter()
+            .map(|c| leak(c))
+            .collect::<Vec<_>>();
+
+        let any_multi_line_comments = multi_line_comments
+            .iter()
+            .chain(&nested_comments)
+            .copied()
+            .collect::<Vec<_>>();
+        let any_comments = any_multi_line_comments
+            .iter()
+            .flat_map(|&(s, e)| [s, e])
+            .chain(line_comments.iter().copied())
+            .collect();
+        let important_syntax = quotes
+            .iter()
+            .chain(&doc_quotes)
+            .chain(&multi_line_comments)
+            .chain(&nested_comments)
+            .map(|&(s, _)| s)
+            .collect();
+
+        Self {
+            name: leak(name),
+            category: definition.category,
+            color: definition
+                .color
+                .as_deref()
+                .filter(|color| is_hex_color(color))
+                .map_or(LanguageType::DEFAULT_COLOR, leak),
+            line_comments: leak_slice(line_comments),
+            multi_line_comments: leak_slice(multi_line_comments),
+            allows_nested: definition.nested,
+            nested_comments: leak_slice(nested_comments),
+            quotes: leak_slice(quotes),
+            verbatim_quotes: leak_slice(leak_pairs(&definition.verbatim_quotes)),
+            doc_quotes: leak_slice(doc_quotes),
+            any_multi_line_comments: leak_slice(any_multi_line_comments),
+            any_comments: leak_slice(any_comments),
+            important_syntax: leak_slice(important_syntax),
+        }
+    }
+}
+
+impl CustomLanguage {
+    /// Registers the language `name` defined by `definition`, returning the
+    /// handle of an identical definition if one was registered before.
+    pub fn register(name: &str, definition: &LanguageDefinition) -> Self {
+        let find = |registry: &Registry| {
+            registry
+                .iter()
+                .position(|(n, d, _)| n == name && d == definition)
+                .map(CustomLanguage)
+        };
+
+        if let Some(language) = find(&REGISTRY.read().unwrap()) {
+            return language;
+        }
+
+        let mut registry = REGISTRY.write().unwrap();
+        find(&registry).unwrap_or_else(|| {
+            let syntax = Box::leak(Box::new(CustomSyntax::new(name, definition)));
+            registry.push((name.to_owned(), definition.clone(), syntax));
+            CustomLanguage(registry.len() - 1)
+        })
+    }
+
+    /// The most recently registered language called `name`.
+    pub(crate) fn find(name: &str) -> Option<Self> {
+        REGISTRY
+            .read()
+            .unwrap()
+            .iter()
+            .rposition(|(n, _, _)| n == name)
+            .map(CustomLanguage)
+    }
+
+    /// The syntax registered for this handle, an empty one for handles that
+    /// weren't made by [`CustomLanguage::register`].
+    pub(crate) fn syntax(self) -> &'static CustomSyntax {
+        REGISTRY
+            .read()
+            .unwrap()
+            .get(self.0)
+            .map_or(&UNKNOWN, |(_, _, syntax)| *syntax)
+    }
+}
+
+/// Finds the custom language of `entry` by its file name or extension.
+pub(crate) fn from_path(
+    languages: &BTreeMap<String, LanguageDefinition>,
+    entry: &Path,
+) -> Option<LanguageType> {
+    let filename = fsutils::get_filename(entry)?;
+    let extension = fsutils::get_extension(entry);
+
+    languages
+        .iter()
+        .find(|(_, definition)| definition.matches(&filename, extension.as_deref()))
+        .map(|(name, definition)| LanguageType::Custom(CustomLanguage::register(name, definition)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::Config;
+
+    fn config() -> Config {
+        toml::from_str(
+            r#"
+            [custom_languages.Flow]
+            extensions = ["flow"]
+            filenames = ["Flowfile"]
+            line_comment = ["--"]
+            multi_line_comments = [["{-", "-}"]]
+            quotes = [["\"", "\""]]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_custom_languages_by_extension_and_filename() {
+        let config = config();
+
+        let by_extension = LanguageType::from_path(Path::new("src/main.FLOW"), &config).unwrap();
+        let by_filename = LanguageType::from_path(Path::new("Flowfile"), &config).unwrap();
+
+        assert_eq!(by_extension, by_filename);
+        assert_eq!(by_extension.name(), "Flow");
+        assert_eq!(by_extension.line_comments(), &["--"]);
+        assert_eq!(by_extension.color(), LanguageType::DEFAULT_COLOR);
+        assert_eq!("Flow".parse::<LanguageType>(), Ok(by_extension));
+        assert_eq!(
+            LanguageType::from_path(Path::new("main.rs"), &config),
+            Some(LanguageType::Rust)
+        );
+    }
+
+    #[test]
+    fn counts_custom_languages_with_their_syntax() {
+        let config = config();
+        let flow = LanguageType::from_path(Path::new("a.flow"), &config).unwrap();
+        let text = "-- note\n{- a\n   b -}\nrun \"--\"\n\n";
+
+        let stats = flow.parse_from_str(text, &config);
+
+        assert_eq!((stats.code, stats.comments, stats.blanks), (1, 3, 1));
+    }
+
+    #[test]
+    fn reuses_handles_of_identical_definitions() {
+        let definition = LanguageDefinition {
+            extensions: vec!["dsl".into()],
+            ..LanguageDefinition::default()
+        };
+        let changed = LanguageDefinition {
+            line_comment: vec!["#".into()],
+            ..definition.clone()
+        };
+
+        let first = CustomLanguage::register("Dsl", &definition);
+
+        assert_eq!(CustomLanguage::register("Dsl", &definition), first);
+        assert_ne!(CustomLanguage::register("Dsl", &changed), first);
+    }
+}
@@ -2,25 +2,141 @@ use std::{
     borrow::Cow,
     fmt,
     fs::File,
-    io::{self, BufRead, BufReader, Read},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    mem,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
 use crate::{
+    classify::FileClass,
     config::Config,
-    language::syntax::{FileContext, LanguageContext, SyntaxCounter},
-    stats::{CodeStats, Report},
-    utils::{ext::SliceExt, fs as fsutils},
+    language::{
+        category::Category,
+        custom::{self, CustomLanguage},
+        syntax::{FileContext, LanguageContext, SyntaxCounter},
+    },
+    stats::{CodeStats, LineKind, Report},
+    utils::{ext::SliceExt, fs as fsutils, gitattributes::Linguist},
 };
 
+use dashmap::DashMap;
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use grep_searcher::{LineIter, LineStep};
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use serde::Serialize;
+use regex::bytes::{Regex, RegexBuilder};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
 use self::LanguageType::*;
 
+/// How much of a file is read to pick its language by
+/// [`LanguageType::heuristics`].
+const HEURISTICS_LEN: u64 = 64 * 1024;
+
+/// How many lines at either end of a file are searched for a modeline, as
+/// many as Vim searches by default.
+const MODELINE_LINES: usize = 5;
+
+/// How much of either end of a file is searched for modelines, and of its
+/// end for an Emacs local variables list.
+const MODELINE_LEN: u64 = 3 * 1024;
+
+/// What's read of a file to pick its language: up to `head_len` bytes of its
+/// start, and its end when the file is too long to be read whole.
+struct Sample<'a> {
+    head: Cow<'a, [u8]>,
+    tail: Option<Vec<u8>>,
+}
+
+impl<'a> Sample<'a> {
+    fn whole(content: &'a [u8]) -> Self {
+        Sample {
+            head: Cow::Borrowed(content),
+            tail: None,
+        }
+    }
+
+    fn owned(content: Vec<u8>) -> Self {
+        Sample {
+            head: Cow::Owned(content),
+            tail: None,
+        }
+    }
+
+    fn read(path: &Path, head_len: u64) -> Option<Sample<'static>> {
+        let mut file = File::open(path).ok()?;
+        let mut head = Vec::new();
+        (&mut file).take(head_len).read_to_end(&mut head).ok()?;
+
+        let rest = file
+            .metadata()
+            .ok()?
+            .len()
+            .saturating_sub(head.len() as u64);
+        let mut tail = None;
+        if rest > 0 {
+            let mut end = Vec::new();
+            file.seek(SeekFrom::End(-(rest.min(MODELINE_LEN) as i64)))
+                .ok()?;
+            file.read_to_end(&mut end).ok()?;
+            tail = Some(end);
+        }
+
+        Some(Sample {
+            head: Cow::Owned(head),
+            tail,
+        })
+    }
+
+    /// The end of the file, all of `head` when the file was read whole.
+    fn tail(&self) -> &[u8] {
+        self.tail.as_deref().unwrap_or(&self.head)
+    }
+
+    /// The language named by a Vim modeline, an Emacs `-*-` line or an Emacs
+    /// local variables list at either end of the file. Binary files, those
+    /// with a NUL byte near their start, have none.
+    fn modeline(&self) -> Option<LanguageType> {
+        static VIM: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?:^|\s)(?:vi|vim|ex)(?:[<=>]?\d+)?:(?:.*?[\s:])?(?:ft|filetype|syntax|syn)=([\w+#-]+)")
+                .unwrap()
+        });
+        static EMACS: Lazy<Regex> = Lazy::new(|| Regex::new(r"-\*-(.*?)-\*-").unwrap());
+        static EMACS_MODE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?i)(?:^|;)\s*mode\s*:\s*([\w+#-]+)").unwrap());
+        static EMACS_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*([\w+#-]+)\s*$").unwrap());
+        static LOCAL_VARIABLES: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?m)Local Variables:(?:.*\n)*?\W*mode:\s*([\w+#-]+)").unwrap()
+        });
+
+        let head = &self.head[..self.head.len().min(MODELINE_LEN as usize)];
+        if head.contains(&0) {
+            return None;
+        }
+        let tail = self.tail();
+        let tail = &tail[tail.len().saturating_sub(MODELINE_LEN as usize)..];
+        let tail = tail.strip_suffix(b"\n").unwrap_or(tail);
+        let mut lines = head
+            .split(|&b| b == b'\n')
+            .take(MODELINE_LINES)
+            .chain(tail.rsplit(|&b| b == b'\n').take(MODELINE_LINES));
+
+        let name = lines
+            .find_map(|line| {
+                VIM.captures(line).or_else(|| {
+                    let variables = EMACS.captures(line)?.get(1)?.as_bytes();
+                    EMACS_MODE
+                        .captures(variables)
+                        .or_else(|| EMACS_NAME.captures(variables))
+                })
+            })
+            .or_else(|| LOCAL_VARIABLES.captures(tail))?;
+
+        LanguageType::from_modeline_name(&String::from_utf8_lossy(&name[1]))
+    }
+}
+
 include!(concat!(env!("OUT_DIR"), "/language_type.rs"));
 
 impl Serialize for LanguageType {
@@ -31,9 +147,50 @@ impl Serialize for LanguageType {
     }
 }
 
+/// Custom languages are read back by name, so they must have been registered
+/// first, see [`Config::register_custom_languages`].
+impl<'de> Deserialize<'de> for LanguageType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        LanguageType::from_serialized_name(&name)
+            .or_else(|| CustomLanguage::find(&name).map(Custom))
+            .ok_or_else(|| de::Error::custom(format!("unknown language: {}", name)))
+    }
+}
+
+/// Whether `color` is written as `#RRGGBB`, like the colors of
+/// `languages.json`.
+pub(crate) fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
 impl LanguageType {
+    /// The color of languages defined without one, or whose name isn't
+    /// known.
+    pub const DEFAULT_COLOR: &'static str = "#EDEDED";
+
+    /// The first language of `rules` whose pattern is found in `content`.
+    fn from_heuristics(rules: &[(Self, &'static str)], content: &[u8]) -> Option<Self> {
+        static PATTERNS: Lazy<DashMap<&'static str, Regex>> = Lazy::new(DashMap::new);
+
+        rules.iter().find_map(|&(language, pattern)| {
+            let regex = PATTERNS.entry(pattern).or_insert_with(|| {
+                RegexBuilder::new(pattern)
+                    .multi_line(true)
+                    .unicode(false)
+                    .build()
+                    .expect("invalid heuristic in languages.json")
+            });
+            regex.is_match(content).then_some(language)
+        })
+    }
+
     /// Parses a given [`Path`] using the [`LanguageType`]. Returning [`Report`]
-    /// on success and giving back ownership of [`PathBuf`] on error.
+    /// on success and giving back ownership of [`PathBuf`] on error. The
+    /// report is classified as generated when its contents look so.
     pub fn parse(self, path: PathBuf, config: &Config) -> Result<Report, (io::Error, PathBuf)> {
         let text = {
             let f = match File::open(&path) {
@@ -52,6 +209,7 @@ impl LanguageType {
         let mut stats = Report::new(path);
 
         stats += self.parse_from_slice(&text, config);
+        stats.class = FileClass::of_content(self, &text);
 
         Ok(stats)
     }
@@ -91,12 +249,13 @@ impl LanguageType {
             let (skippable_text, rest) = text.split_at(end + 1);
             let is_fortran = syntax.shared.is_fortran;
             let is_literate = syntax.shared.is_literate;
-            let comments = syntax.shared.line_comments;
+            let shared = syntax.shared.clone();
             trace!(
                 "Using Simple Parse on {:?}",
                 String::from_utf8_lossy(skippable_text)
             );
-            let parse_lines = move || self.parse_lines(config, rest, CodeStats::new(), syntax);
+            let parse_lines =
+                move || self.parse_lines(config, rest, CodeStats::new(), syntax, |_, _| {});
             let simple_parse = move || {
                 LineIter::new(b'\n', skippable_text)
                     .par_bridge()
@@ -106,29 +265,91 @@ impl LanguageType {
                         // could cause a miscount.
                         let line = if is_fortran { line } else { line.trim() };
                         if line.trim().is_empty() {
-                            (1, 0, 0)
+                            (1, 0, 0, 0, 0)
+                        } else if shared.starts_with_doc_comment(line) {
+                            (0, 0, 0, 1, 0)
                         } else if is_literate
-                            || comments.iter().any(|c| line.starts_with(c.as_bytes()))
+                            || shared
+                                .line_comments
+                                .iter()
+                                .any(|c| line.starts_with(c.as_bytes()))
                         {
-                            (0, 0, 1)
+                            (0, 0, 1, 0, 0)
                         } else {
-                            (0, 1, 0)
+                            (0, 1, 0, 0, shared.count_branches(line))
                         }
                     })
-                    .reduce(|| (0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+                    .reduce(
+                        || (0, 0, 0, 0, 0),
+                        |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2, a.3 + b.3, a.4 + b.4),
+                    )
             };
 
-            let (mut stats, (blanks, code, comments)) = rayon::join(parse_lines, simple_parse);
+            let (mut stats, (blanks, code, comments, docs, complexity)) =
+                rayon::join(parse_lines, simple_parse);
 
             stats.blanks += blanks;
             stats.code += code;
             stats.comments += comments;
+            stats.docs += docs;
+            stats.complexity += complexity;
             stats
         } else {
-            self.parse_lines(config, text, CodeStats::new(), syntax)
+            self.parse_lines(config, text, CodeStats::new(), syntax, |_, _| {})
+        }
+    }
+
+    /// Classifies every line of the text provided as the given
+    /// [`LanguageType`], in the same way [`LanguageType::parse_from_slice`]
+    /// counts them. Lines of a language embedded in this one, such as the
+    /// code blocks of Markdown, are classified as code unless blank, as are
+    /// the lines of Jupyter notebooks.
+    ///
+    /// ```
+    /// use tokei::{Config, LanguageType, LineKind};
+    ///
+    /// let kinds = LanguageType::Rust.line_kinds(b"// main\n\nfn main() {}\n", &Config::default());
+    ///
+    /// assert_eq!(kinds, [LineKind::Comment, LineKind::Blank, LineKind::Code]);
+    /// ```
+    #[must_use]
+    pub fn line_kinds<A: AsRef<[u8]>>(self, text: A, config: &Config) -> Vec<LineKind> {
+        let text = text.as_ref();
+        let mut kinds = Vec::new();
+
+        if self == Jupyter {
+            for line in LineIter::new(b'\n', text) {
+                kinds.push(if line.trim().is_empty() {
+                    LineKind::Blank
+                } else {
+                    LineKind::Code
+                });
+            }
+            return kinds;
         }
+
+        // Lines are reported in order, but a line that ends an embedded
+        // language can be reported twice, so only its first kind is kept.
+        let mut position = 0;
+        let mut line = 0;
+        let syntax = SyntaxCounter::new(self);
+        self.parse_lines(config, text, CodeStats::new(), syntax, |start, kind| {
+            line += text[position..start]
+                .iter()
+                .filter(|&&c| c == b'\n')
+                .count();
+            position = start;
+            if kinds.len() <= line {
+                kinds.resize(line, LineKind::Blank);
+                kinds.push(kind);
+            }
+        });
+
+        kinds
     }
 
+    /// Counts the lines of the text, calling `on_line` with the position and
+    /// kind of each line as it is counted.
     #[inline]
     fn parse_lines(
         self,
@@ -136,6 +357,7 @@ impl LanguageType {
         lines: &[u8],
         mut stats: CodeStats,
         mut syntax: SyntaxCounter,
+        mut on_line: impl FnMut(usize, LineKind),
     ) -> CodeStats {
         let mut stepper = LineStep::new(b'\n', 0, lines.len());
 
@@ -151,21 +373,24 @@ impl LanguageType {
             };
             trace!("{}", String::from_utf8_lossy(line));
 
-            if syntax.try_perform_single_line_analysis(line, &mut stats) {
+            if let Some(kind) = syntax.try_perform_single_line_analysis(line, &mut stats) {
+                on_line(start, kind);
                 continue;
             }
 
-            let started_in_comments = !syntax.stack.is_empty()
-                || (config.treat_doc_strings_as_comments == Some(true)
-                    && syntax.quote.is_some()
-                    && syntax.quote_is_doc_quote);
+            let started_in_doc_string = config.treat_doc_strings_as_comments == Some(true)
+                && syntax.quote.is_some()
+                && syntax.quote_is_doc_quote;
+            let started_in_comments = !syntax.stack.is_empty() || started_in_doc_string;
+            let started_in_docs =
+                started_in_doc_string || (!syntax.stack.is_empty() && syntax.comment_is_doc);
             let ended_with_comments =
                 match syntax.perform_multi_line_analysis(lines, start, end, config) {
                     crate::language::syntax::AnalysisReport::Normal(end) => end,
                     crate::language::syntax::AnalysisReport::ChildLanguage(FileContext {
                         language,
                         end,
-                        stats: blob,
+                        stats: mut blob,
                     }) => {
                         match language {
                             LanguageContext::Markdown { balanced, language } => {
@@ -175,7 +400,9 @@ impl LanguageType {
                                 *stats.blobs.entry(language).or_default() += blob;
                             }
                             LanguageContext::Rust => {
-                                // Add all the markdown blobs.
+                                // Add all the markdown blobs, their text being
+                                // the documentation.
+                                blob.docs += mem::take(&mut blob.comments);
                                 *stats.blobs.entry(Markdown).or_default() += blob;
                             }
                             LanguageContext::Html { language } => {
@@ -185,6 +412,19 @@ impl LanguageType {
                             }
                         }
 
+                        let mut position = start;
+                        for line in LineIter::new(b'\n', &lines[start..end]) {
+                            on_line(
+                                position,
+                                if line.trim().is_empty() {
+                                    LineKind::Blank
+                                } else {
+                                    LineKind::Code
+                                },
+                            );
+                            position += line.len();
+                        }
+
                         // Advance to after the language code and the delimiter..
                         stepper = LineStep::new(b'\n', end, lines.len());
                         continue;
@@ -192,18 +432,26 @@ impl LanguageType {
                 };
             trace!("{}", String::from_utf8_lossy(line));
 
-            if syntax.shared.is_literate
-                || syntax.line_is_comment(line, config, ended_with_comments, started_in_comments)
-            {
+            let is_comment = syntax.shared.is_literate
+                || syntax.line_is_comment(line, config, ended_with_comments, started_in_comments);
+
+            if is_comment && syntax.line_is_doc(line, config, started_in_docs) {
+                stats.docs += 1;
+                trace!("Doc No.{}", stats.docs);
+                on_line(start, LineKind::Doc);
+            } else if is_comment {
                 stats.comments += 1;
                 trace!("Comment No.{}", stats.comments);
                 trace!("Was the Comment stack empty?: {}", !started_in_comments);
+                on_line(start, LineKind::Comment);
             } else {
                 stats.code += 1;
                 trace!("Code No.{}", stats.code);
+                on_line(start, LineKind::Code);
             }
         }
 
+        stats.complexity += syntax.complexity;
         stats
     }
 
@@ -288,6 +536,156 @@ mod tests {
         assert!(Rust.allows_nested());
     }
 
+    #[test]
+    fn line_kinds_agree_with_counts() {
+        let text = "/* multi\n * line */\nfn main() {\n\n    // say hi\n    println!(\"/*\");\n}\n";
+        let config = Config::default();
+        let mut stats = CodeStats::new();
+        for kind in Rust.line_kinds(text, &config) {
+            stats.add_line(kind);
+        }
+
+        assert_eq!(stats, Rust.parse_from_str(text, &config));
+    }
+
+    #[test]
+    fn line_kinds_cover_embedded_languages() {
+        let text = "# Title\n\n```rust\nfn main() {}\n\n```\nThe end\n";
+
+        assert_eq!(
+            Markdown.line_kinds(text, &Config::default()),
+            [
+                LineKind::Comment,
+                LineKind::Blank,
+                LineKind::Code,
+                LineKind::Code,
+                LineKind::Blank,
+                LineKind::Code,
+                LineKind::Comment,
+            ]
+        );
+    }
+
+    #[test]
+    fn complexity_counts_branches_in_code_only() {
+        let text = "fn notify(a: bool) -> u8 {\n    if a && !a { 1 } else { 0 } // if\n}\n\
+                    /* while || */\nfn f(x: u8) {\n    let s = \"for &&\";\n    \
+                    for _ in 0..x { match x { 0 => (), _ => () } }\n}\n";
+        let stats = Rust.parse_from_str(text, &Config::default());
+
+        assert_eq!(stats.complexity, 5);
+    }
+
+    #[test]
+    fn complexity_keeps_longest_keyword() {
+        let text = "if a:\n    pass\nelif b or c:\n    pass\n";
+
+        let stats = Python.parse_from_str(text, &Config::default());
+
+        assert_eq!(stats.complexity, 3);
+    }
+
+    #[test]
+    fn ambiguous_extensions_are_picked_by_content() {
+        let cases = [
+            ("a.h", "@interface A : NSObject\n@end\n", ObjectiveC),
+            ("a.h", "namespace a {\nclass B {};\n}\n", CppHeader),
+            ("a.h", "int add(int a, int b);\n", CHeader),
+            ("a.m", "% plots\nx = linspace(0, 1);\n", Matlab),
+            ("a.pl", "parent(tom, bob).\nchild(X) :- parent(_, X).\n", Prolog),
+            ("a.pl", "use strict;\nmy $a = 1;\n", Perl),
+            ("a.v", "module top(input a);\nendmodule\n", Verilog),
+            ("a.v", "Lemma a : True.\nProof. auto. Qed.\n", Coq),
+        ];
+
+        for (path, content, language) in cases {
+            let found =
+                LanguageType::from_path_and_content(path, content.as_bytes(), &Config::default());
+            assert_eq!(found, Some(language), "{}", content);
+        }
+    }
+
+    #[test]
+    fn modelines_name_the_language() {
+        let cases = [
+            ("run", "#!/bin/sh\n# vim: set ft=bash :\necho\n", Some(Bash)),
+            (
+                "setup.tmpl",
+                "# -*- mode: python; coding: utf-8 -*-\nx = 1\n",
+                Some(Python),
+            ),
+            ("a.h", "/* -*- C++ -*- */\nint a;\n", Some(Cpp)),
+            (
+                "notes",
+                "a\n\n\n\n\n\nb\n// vim:ft=cpp.doxygen\n",
+                Some(Cpp),
+            ),
+            (
+                "init",
+                "(a)\n;; Local Variables:\n;; mode: emacs-lisp\n;; End:\n",
+                Some(Elisp),
+            ),
+            ("main.tmpl", "// vim: ft=c\nint main() {}\n", Some(C)),
+            ("main.txt", "// vim: ft=c\nint main() {}\n", Some(Text)),
+            ("Makefile", "# -*- mode: python -*-\n", Some(Python)),
+            ("main.rs", "fn main() {}\n", Some(Rust)),
+            ("a.bin", "\0# vim: ft=c\n", None),
+            ("notes", "vim: is an editor\n", None),
+        ];
+
+        for (path, content, language) in cases {
+            let found =
+                LanguageType::from_path_and_content(path, content.as_bytes(), &Config::default());
+            assert_eq!(found, language, "{}", content);
+        }
+    }
+
+    #[test]
+    fn files_are_not_read_when_the_extension_settles_the_language() {
+        let config = Config::default();
+        let unread = || -> Option<Vec<u8>> { unreachable!("read a file named by its extension") };
+        let found =
+            LanguageType::from_path_and_loaded_content(Path::new("main.rs"), &config, unread);
+        assert_eq!(found, Some(Rust));
+
+        let found = LanguageType::from_path_and_loaded_content(Path::new("a.h"), &config, || {
+            Some(b"namespace a {}\n".to_vec())
+        });
+        assert_eq!(found, Some(CppHeader));
+    }
+
+    #[test]
+    fn modelines_are_read_from_the_end_of_long_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("script");
+        let body = "x\n".repeat(HEURISTICS_LEN as usize);
+        std::fs::write(&path, format!("{}# vim: ft=ruby\n", body)).unwrap();
+
+        assert_eq!(LanguageType::from_modeline(&path), Some(Ruby));
+    }
+
+    #[test]
+    fn doc_comments_are_counted_apart_from_comments() {
+        let text = "/**\n * Adds.\n */\n/* plain */\n// note\nint add() { return 1; }\n";
+
+        let stats = Java.parse_from_str(text, &Config::default());
+
+        assert_eq!((stats.code, stats.comments, stats.docs), (1, 2, 3));
+    }
+
+    #[test]
+    fn doc_strings_count_as_docs_when_treated_as_comments() {
+        let text = "def f():\n    \"\"\"Does.\n    nothing\"\"\"\n    # note\n    pass\n";
+        let config = Config {
+            treat_doc_strings_as_comments: Some(true),
+            ..Config::default()
+        };
+
+        let stats = Python.parse_from_str(text, &config);
+
+        assert_eq!((stats.code, stats.comments, stats.docs), (2, 1, 2));
+    }
+
     #[test]
     fn jupyter_notebook_has_correct_totals() {
         let sample_notebook =
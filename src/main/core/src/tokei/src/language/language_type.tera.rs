@@ -4,14 +4,15 @@ use arbitrary::Arbitrary;
 /// information about the language, such as multi line comments, single line
 /// comments, string literal syntax, whether a given language allows nesting
 /// comments.
-#[derive(Deserialize)]
 #[derive(Arbitrary, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[non_exhaustive]
 #[allow(clippy::upper_case_acronyms)]
 pub enum LanguageType {
     {% for key, value in languages -%}
-        #[allow(missing_docs)] {% if value.name is defined %} #[serde(alias = "{{value.name}}")] {% else %} #[serde(alias = "{{key}}")] {% endif %} {{key}},
+        #[allow(missing_docs)] {{key}},
     {% endfor %}
+    /// A language defined at runtime, see [`Config::custom_languages`].
+    Custom(CustomLanguage),
 }
 
 impl LanguageType {
@@ -29,6 +30,49 @@ impl LanguageType {
             {% for key, value in languages -%}
                 {{key}} => {% if value.name %}"{{value.name}}"{% else %}"{{key}}"{% endif %},
             {% endfor %}
+            Custom(custom) => custom.syntax().name,
+        }
+    }
+
+    /// Returns the category of a language.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// assert_eq!(LanguageType::Json.category(), Category::Data);
+    /// ```
+    pub fn category(self) -> Category {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => Category::{{value.category | capitalize}},
+            {% endfor %}
+            Custom(custom) => custom.syntax().category,
+        }
+    }
+
+    /// Returns the color of a language as `#RRGGBB`, the one GitHub shows
+    /// it in where it has one. See [`Config::color`] for overriding it.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// assert_eq!(LanguageType::Rust.color(), "#dea584");
+    /// ```
+    pub fn color(self) -> &'static str {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => "{{value.color}}",
+            {% endfor %}
+            Custom(custom) => custom.syntax().color,
+        }
+    }
+
+    /// The language serialized as `name`, by its key in `languages.json`
+    /// or its display name.
+    fn from_serialized_name(name: &str) -> Option<Self> {
+        match name {
+            {% for key, value in languages -%}
+                "{{key}}" {% if value.name and value.name != key %} | "{{value.name}}" {% endif %} => Some({{key}}),
+            {% endfor %}
+            _ => None,
         }
     }
 
@@ -37,6 +81,7 @@ impl LanguageType {
             {% for key, v in languages -%}
                 {{key}} => {{ v.blank | default(value=false) }},
             {% endfor %}
+            Custom(_) => false,
         }
     }
 
@@ -53,6 +98,7 @@ impl LanguageType {
             {% for key, v in languages -%}
                 {{key}} => {{ v.literate | default(value=false) }},
             {% endfor %}
+            Custom(_) => false,
         }
     }
 
@@ -77,6 +123,7 @@ impl LanguageType {
             {% for key, value in languages -%}
                 {{key}} => &[{% for item in value.line_comment | default(value=[]) %}"{{item}}",{% endfor %}],
             {% endfor %}
+            Custom(custom) => custom.syntax().line_comments,
         }
     }
 
@@ -96,6 +143,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().multi_line_comments,
         }
     }
 
@@ -111,6 +159,7 @@ impl LanguageType {
             {% for key, v in languages -%}
                 {{key}} => {{ v.nested | default(value=false) }},
             {% endfor %}
+            Custom(custom) => custom.syntax().allows_nested,
         }
     }
 
@@ -131,6 +180,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().nested_comments,
         }
     }
 
@@ -149,6 +199,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().quotes,
         }
     }
 
@@ -167,6 +218,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().verbatim_quotes,
         }
     }
 
@@ -185,6 +237,7 @@ impl LanguageType {
                     {%- endfor %}
                 ],
             {%- endfor %}
+            Custom(custom) => custom.syntax().doc_quotes,
         }
     }
 
@@ -199,6 +252,39 @@ impl LanguageType {
             {% for key, lang in languages -%}
                 {{key}} => &[{% for item in lang.shebangs | default(value=[]) %}"{{item}}",{% endfor %}],
             {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    /// Returns the starts of the line and multi-line comments of a language
+    /// that document the code, such as `///` and `/**`.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Java;
+    /// assert_eq!(lang.doc_comments(), &["/**"]);
+    /// ```
+    pub fn doc_comments(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.doc_comments | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(_) => &[],
+        }
+    }
+
+    /// Returns the keywords and operators that branch the code of a language,
+    /// which are counted towards its complexity.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Go;
+    /// assert_eq!(lang.complexity(), &["if", "for", "case", "&&", "||"]);
+    /// ```
+    pub fn complexity(self) -> &'static [&'static str] {
+        match self {
+            {% for key, value in languages -%}
+                {{key}} => &[{% for item in value.complexity | default(value=[]) %}"{{item}}",{% endfor %}],
+            {% endfor %}
+            Custom(_) => &[],
         }
     }
 
@@ -213,6 +299,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().any_multi_line_comments,
         }
     }
 
@@ -232,6 +319,7 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().any_comments,
         }
     }
 
@@ -261,10 +349,29 @@ impl LanguageType {
                     {%- endfor -%}
                 ],
             {% endfor %}
+            Custom(custom) => custom.syntax().important_syntax,
         }
     }
 
-    /// Get language from a file path. May open and read the file.
+    /// Returns the content heuristics of a file extension shared by several
+    /// languages, each a language and a regex tried in order against the
+    /// start of the file. Empty for extensions that aren't ambiguous.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let rules = LanguageType::heuristics("v");
+    /// assert_eq!(rules[1].0, LanguageType::Verilog);
+    /// ```
+    pub fn heuristics(extension: &str) -> &'static [(Self, &'static str)] {
+        match extension {
+            {% for extension, rules in heuristics -%}
+                "{{extension}}" => &[{% for rule in rules %}({{rule.language}}, r#"{{rule.pattern}}"#),{% endfor %}],
+            {% endfor %}
+            _ => &[],
+        }
+    }
+
+    /// Get language from a file path. May open and read the file, and the
+    /// `.gitattributes` files of its repository for a `linguist-language`.
     ///
     /// ```no_run
     /// use tokei::{Config, LanguageType};
@@ -273,10 +380,84 @@ impl LanguageType {
     ///
     /// assert_eq!(rust, Some(LanguageType::Rust));
     /// ```
-    pub fn from_path<P: AsRef<Path>>(entry: P, _config: &Config)
+    pub fn from_path<P: AsRef<Path>>(entry: P, config: &Config)
         -> Option<Self>
     {
         let entry = entry.as_ref();
+        Self::from_path_and_linguist(entry, config, Linguist::of(entry).language)
+    }
+
+    /// Get language from a file path, with the `linguist-language` of the
+    /// file already read from its `.gitattributes`.
+    pub(crate) fn from_path_and_linguist(entry: &Path, config: &Config, linguist: Option<Self>)
+        -> Option<Self>
+    {
+        Self::from_path_with(entry, config, linguist, |head_len| Sample::read(entry, head_len))
+    }
+
+    /// Get language from the path of a file that isn't on disk, such as a
+    /// file in a git revision, looking at its `content` for a modeline, a
+    /// shebang or heuristics when the path has no extension, an unknown one
+    /// or an ambiguous one.
+    ///
+    /// ```
+    /// use tokei::{Config, LanguageType};
+    ///
+    /// let config = Config::default();
+    /// let script = LanguageType::from_path_and_content("bin/run", b"#!/bin/sh\n", &config);
+    ///
+    /// assert_eq!(script, Some(LanguageType::Sh));
+    /// ```
+    pub fn from_path_and_content<P: AsRef<Path>>(entry: P, content: &[u8], config: &Config)
+        -> Option<Self>
+    {
+        Self::from_path_with(entry.as_ref(), config, None, |_| Some(Sample::whole(content)))
+    }
+
+    /// Like [`LanguageType::from_path_and_content`], only loading the content
+    /// when the path doesn't settle the language.
+    pub(crate) fn from_path_and_loaded_content(
+        entry: &Path,
+        config: &Config,
+        load: impl FnOnce() -> Option<Vec<u8>>,
+    ) -> Option<Self>
+    {
+        Self::from_path_with(entry, config, None, |_| load().map(Sample::owned))
+    }
+
+    fn from_path_with<'a>(
+        entry: &Path,
+        config: &Config,
+        linguist: Option<Self>,
+        content: impl FnOnce(u64) -> Option<Sample<'a>>,
+    ) -> Option<Self>
+    {
+        if let Some(custom) = config
+            .custom_languages
+            .as_ref()
+            .and_then(|languages| custom::from_path(languages, entry))
+        {
+            return Some(custom);
+        }
+
+        if let Some(language) = config.language_override(entry).or(linguist) {
+            return Some(language);
+        }
+
+        // A file is only read when its extension doesn't settle the language,
+        // there modelines are trusted over the file name, as in Linguist, and
+        // only ambiguous extensions need more of the file than its ends.
+        let extension = fsutils::get_extension(entry);
+        let rules = extension.as_deref().map_or(&[][..], LanguageType::heuristics);
+        let by_extension = extension.as_deref().and_then(LanguageType::from_file_extension);
+        let sample = match (by_extension, rules.is_empty()) {
+            (Some(_), true) => None,
+            (None, true) => content(MODELINE_LEN),
+            (_, false) => content(HEURISTICS_LEN),
+        };
+        if let Some(language) = sample.as_ref().and_then(Sample::modeline) {
+            return Some(language);
+        }
 
         if let Some(filename) = fsutils::get_filename(entry) {
             match &*filename {
@@ -292,9 +473,18 @@ impl LanguageType {
             }
         }
 
-        match fsutils::get_extension(entry) {
-            Some(extension) => LanguageType::from_file_extension(extension.as_str()),
-            None => LanguageType::from_shebang(&entry),
+        if by_extension.is_some() && rules.is_empty() {
+            return by_extension;
+        }
+
+        match extension {
+            Some(_) => sample
+                .and_then(|sample| LanguageType::from_heuristics(rules, &sample.head))
+                .or(by_extension),
+            None => sample.and_then(|sample| {
+                let line = sample.head.split(|b| *b == b'\n').next().unwrap_or_default();
+                LanguageType::from_shebang_line(&String::from_utf8_lossy(line))
+            }),
         }
     }
 
@@ -365,6 +555,38 @@ impl LanguageType {
         let mut line = String::new();
         let _ = buf.read_line(&mut line);
 
+        Self::from_shebang_line(&line)
+    }
+
+    /// Get language from a Vim or Emacs modeline in the first or last lines
+    /// of a file. May open and read the file.
+    ///
+    /// ```no_run
+    /// use tokei::LanguageType;
+    ///
+    /// // setup.tmpl: `# vim: set ft=python :`
+    /// let python = LanguageType::from_modeline("./setup.tmpl");
+    ///
+    /// assert_eq!(python, Some(LanguageType::Python));
+    /// ```
+    pub fn from_modeline<P: AsRef<Path>>(entry: P) -> Option<Self> {
+        Sample::read(entry.as_ref(), MODELINE_LEN)?.modeline()
+    }
+
+    /// Get language from the filetype of a Vim modeline or the mode of an
+    /// Emacs one, by the aliases of the language or else its name.
+    fn from_modeline_name(name: &str) -> Option<Self> {
+        match &*name.to_lowercase() {
+            {% for key, value in languages -%}
+                {%- if value.aliases -%}
+                    {%- for item in value.aliases  %}| "{{item}}" {% endfor %}=> Some({{key}}),
+                {% endif -%}
+            {%- endfor %}
+            name => LanguageType::from_str(name).ok(),
+        }
+    }
+
+    fn from_shebang_line(line: &str) -> Option<Self> {
         let mut words = line.split_whitespace();
         match words.next() {
             {# First match against any shebang paths, and then check if the
@@ -412,8 +634,10 @@ impl FromStr for LanguageType {
                 {% if value.name %}"{{value.name | lower}}"{% else %}"{{key | lower}}"{% endif %}
                 => Ok({{key}}),
             {% endfor %}
-            _ => Err("Language not found, please use `-l` to see all available\
-                     languages."),
+            _ => CustomLanguage::find(from).map(Custom).ok_or(
+                "Language not found, please use `-l` to see all available\
+                 languages.",
+            ),
         }
     }
 }
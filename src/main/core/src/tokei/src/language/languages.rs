@@ -9,7 +9,7 @@ use rayon::prelude::*;
 
 use crate::{
     config::Config,
-    language::{Language, LanguageType},
+    language::{Category, Language, LanguageType},
     utils,
 };
 
@@ -86,6 +86,39 @@ impl Languages {
         self.inner.par_iter_mut().for_each(|(_, l)| l.total());
     }
 
+    /// Populates the `Languages` struct with the files below `path`, the root
+    /// or any directory of a git repository, as they are in `revision`,
+    /// without checking it out. `revision` is anything git accepts, such as a
+    /// tag, a branch or a commit hash. Ignore files are read from the revision
+    /// itself and `ignored` works like in [`Languages::get_statistics`].
+    /// Reports are named as if the revision was checked out.
+    ///
+    /// ```no_run
+    /// use tokei::{Config, Languages};
+    ///
+    /// let mut languages = Languages::new();
+    /// languages
+    ///     .get_statistics_at_revision(".", "v12.1.2", &[], &Config::default())
+    ///     .unwrap();
+    /// ```
+    pub fn get_statistics_at_revision<A: AsRef<Path>>(
+        &mut self,
+        path: A,
+        revision: &str,
+        ignored: &[&str],
+        config: &Config,
+    ) -> Result<(), git2::Error> {
+        utils::git::get_all_files_at_revision(
+            path.as_ref(),
+            revision,
+            ignored,
+            &mut self.inner,
+            config,
+        )?;
+        self.inner.par_iter_mut().for_each(|(_, l)| l.total());
+        Ok(())
+    }
+
     /// Constructs a new, Languages struct. Languages is always empty and does
     /// not allocate.
     ///
@@ -101,14 +134,43 @@ impl Languages {
     /// Summary of the Languages struct.
     #[must_use]
     pub fn total(self: &Languages) -> Language {
+        self.total_where(|_| true)
+    }
+
+    /// Summary of the Languages struct for each category of languages, see
+    /// [`LanguageType::category`]. Categories without any languages are left
+    /// out.
+    ///
+    /// ```no_run
+    /// use tokei::{Category, Config, Languages};
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["."], &[], &Config::default());
+    ///
+    /// let totals = languages.total_by_category();
+    /// let code = totals.get(&Category::Programming).map_or(0, |total| total.code);
+    /// ```
+    #[must_use]
+    pub fn total_by_category(&self) -> BTreeMap<Category, Language> {
+        Category::list()
+            .iter()
+            .filter(|&&category| self.keys().any(|ty| ty.category() == category))
+            .map(|&category| (category, self.total_where(|ty| ty.category() == category)))
+            .collect()
+    }
+
+    fn total_where(&self, counted: impl Fn(LanguageType) -> bool) -> Language {
         let mut total = Language::new();
-        for (ty, l) in self {
+        for (ty, l) in self.iter().filter(|(ty, _)| counted(**ty)) {
             let language = l.summarise();
             total.comments += language.comments;
             total.blanks += language.blanks;
             total.code += language.code;
+            total.docs += language.docs;
+            total.complexity += language.complexity;
             total.inaccurate |= language.inaccurate;
             total.children.insert(*ty, language.reports.clone());
+            total.generated.extend(language.generated);
         }
         total
     }
@@ -164,3 +226,36 @@ impl DerefMut for Languages {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_are_split_by_category() {
+        let mut languages = Languages::new();
+        for (language, code) in [
+            (LanguageType::Rust, 10),
+            (LanguageType::Python, 5),
+            (LanguageType::Json, 7),
+        ] {
+            languages.insert(
+                language,
+                Language {
+                    code,
+                    ..Language::new()
+                },
+            );
+        }
+
+        let totals = languages.total_by_category();
+
+        assert_eq!(
+            totals.keys().copied().collect::<Vec<_>>(),
+            [Category::Programming, Category::Data]
+        );
+        assert_eq!(totals[&Category::Programming].code, 15);
+        assert_eq!(totals[&Category::Data].code, 7);
+        assert_eq!(languages.total().code, 22);
+    }
+}
@@ -1,3 +1,5 @@
+mod category;
+mod custom;
 mod embedding;
 pub mod language_type;
 pub mod languages;
@@ -5,7 +7,12 @@ mod syntax;
 
 use std::{collections::BTreeMap, mem, ops::AddAssign};
 
-pub use self::{language_type::*, languages::Languages};
+pub use self::{
+    category::Category,
+    custom::{CustomLanguage, LanguageDefinition},
+    language_type::*,
+    languages::Languages,
+};
 
 use crate::{sort::Sort, stats::Report};
 
@@ -18,8 +25,18 @@ pub struct Language {
     pub code: usize,
     /// The total number of comments(both single, and multi-line)
     pub comments: usize,
+    /// The total number of documentation comments.
+    #[serde(default)]
+    pub docs: usize,
+    /// The total number of branch keywords and operators in the code.
+    #[serde(default)]
+    pub complexity: usize,
     /// A collection of statistics of individual files.
     pub reports: Vec<Report>,
+    /// Reports of generated and vendored files, see [`Report::class`]. They
+    /// aren't counted in the totals of the language.
+    #[serde(default)]
+    pub generated: Vec<Report>,
     /// A map of any languages found in the reports.
     pub children: BTreeMap<LanguageType, Vec<Report>>,
     /// Whether this language had problems with file parsing
@@ -42,12 +59,18 @@ impl Language {
     #[inline]
     #[must_use]
     pub fn lines(&self) -> usize {
-        self.blanks + self.code + self.comments
+        self.blanks + self.code + self.comments + self.docs
     }
 
-    /// Add a `Report` to the Language. This will not update the totals in the
-    /// Language struct.
+    /// Add a `Report` to the Language, or to its generated files if the
+    /// report has a class. This will not update the totals in the Language
+    /// struct.
     pub fn add_report(&mut self, report: Report) {
+        if report.class.is_some() {
+            self.generated.push(report);
+            return;
+        }
+
         for (lang, stats) in &report.stats.blobs {
             let mut new_report = Report::new(report.name.clone());
             new_report.stats = stats.clone();
@@ -77,6 +100,8 @@ impl Language {
                 summary.comments += stats.comments;
                 summary.code += stats.code;
                 summary.blanks += stats.blanks;
+                summary.docs += stats.docs;
+                summary.complexity += stats.complexity;
             }
         }
 
@@ -104,16 +129,22 @@ impl Language {
         let mut blanks = 0;
         let mut code = 0;
         let mut comments = 0;
+        let mut docs = 0;
+        let mut complexity = 0;
 
         for report in &self.reports {
             blanks += report.stats.blanks;
             code += report.stats.code;
             comments += report.stats.comments;
+            docs += report.stats.docs;
+            complexity += report.stats.complexity;
         }
 
         self.blanks = blanks;
         self.code = code;
         self.comments = comments;
+        self.docs = docs;
+        self.complexity = complexity;
     }
 
     /// Checks if the language is empty. Empty meaning it doesn't have any
@@ -127,7 +158,11 @@ impl Language {
     /// ```
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.code == 0 && self.comments == 0 && self.blanks == 0 && self.children.is_empty()
+        self.code == 0
+            && self.comments == 0
+            && self.docs == 0
+            && self.blanks == 0
+            && self.children.is_empty()
     }
 
     /// Sorts each of the `Report`s contained in the language based
@@ -148,28 +183,20 @@ impl Language {
     /// assert_eq!(8, language.reports[0].stats.code);
     /// ```
     pub fn sort_by(&mut self, category: Sort) {
-        match category {
-            Sort::Blanks => self
-                .reports
-                .sort_by(|a, b| b.stats.blanks.cmp(&a.stats.blanks)),
-            Sort::Comments => self
-                .reports
-                .sort_by(|a, b| b.stats.comments.cmp(&a.stats.comments)),
-            Sort::Code => self.reports.sort_by(|a, b| b.stats.code.cmp(&a.stats.code)),
-            Sort::Files => self.reports.sort_by(|a, b| a.name.cmp(&b.name)),
-            Sort::Lines => self
-                .reports
-                .sort_by(|a, b| b.stats.lines().cmp(&a.stats.lines())),
-        }
+        self.reports.sort_by(|a, b| category.cmp_reports(a, b));
+        self.generated.sort_by(|a, b| category.cmp_reports(a, b));
     }
 }
 
 impl AddAssign for Language {
     fn add_assign(&mut self, mut rhs: Self) {
         self.comments += rhs.comments;
+        self.docs += rhs.docs;
         self.blanks += rhs.blanks;
         self.code += rhs.code;
+        self.complexity += rhs.complexity;
         self.reports.extend(mem::take(&mut rhs.reports));
+        self.generated.extend(mem::take(&mut rhs.generated));
         self.children.extend(mem::take(&mut rhs.children));
         self.inaccurate |= rhs.inaccurate;
     }
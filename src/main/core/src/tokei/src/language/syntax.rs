@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use aho_corasick::AhoCorasick;
+use aho_corasick::{AhoCorasick, Anchored, Input};
 use dashmap::DashMap;
 use grep_searcher::LineStep;
 use log::Level::Trace;
@@ -9,7 +9,11 @@ use once_cell::sync::Lazy;
 use super::embedding::{
     RegexCache, RegexFamily, ENDING_MARKDOWN_REGEX, END_SCRIPT, END_STYLE, END_TEMPLATE,
 };
-use crate::{stats::CodeStats, utils::ext::SliceExt, Config, LanguageType};
+use crate::{
+    stats::{CodeStats, LineKind},
+    utils::ext::SliceExt,
+    Config, LanguageType,
+};
 
 /// Tracks the syntax of the language as well as the current state in the file.
 /// Current has what could be consider three types of mode.
@@ -29,6 +33,8 @@ pub(crate) struct SyntaxCounter {
     pub(crate) quote_is_doc_quote: bool,
     pub(crate) stack: Vec<&'static str>,
     pub(crate) quote_is_verbatim: bool,
+    pub(crate) comment_is_doc: bool,
+    pub(crate) complexity: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -65,6 +71,7 @@ pub(crate) struct SharedMatchers {
     pub language: LanguageType,
     pub allows_nested: bool,
     pub doc_quotes: &'static [(&'static str, &'static str)],
+    pub doc_comments: &'static [&'static str],
     pub important_syntax: AhoCorasick,
     #[allow(dead_code)]
     pub any_comments: &'static [&'static str],
@@ -76,6 +83,7 @@ pub(crate) struct SharedMatchers {
     pub nested_comments: &'static [(&'static str, &'static str)],
     pub string_literals: &'static [(&'static str, &'static str)],
     pub verbatim_string_literals: &'static [(&'static str, &'static str)],
+    pub complexity: AhoCorasick,
 }
 
 impl SharedMatchers {
@@ -90,10 +98,10 @@ impl SharedMatchers {
     }
 
     pub fn init(language: LanguageType) -> Self {
-        fn init_corasick(pattern: &[&'static str]) -> AhoCorasick {
+        fn init_corasick(pattern: &[&'static str], start: aho_corasick::StartKind) -> AhoCorasick {
             AhoCorasick::builder()
                 .match_kind(aho_corasick::MatchKind::LeftmostLongest)
-                .start_kind(aho_corasick::StartKind::Unanchored)
+                .start_kind(start)
                 .prefilter(true)
                 .kind(Some(aho_corasick::AhoCorasickKind::DFA))
                 .build(pattern)
@@ -104,9 +112,13 @@ impl SharedMatchers {
             language,
             allows_nested: language.allows_nested(),
             doc_quotes: language.doc_quotes(),
+            doc_comments: language.doc_comments(),
             is_fortran: language.is_fortran(),
             is_literate: language.is_literate(),
-            important_syntax: init_corasick(language.important_syntax()),
+            important_syntax: init_corasick(
+                language.important_syntax(),
+                aho_corasick::StartKind::Unanchored,
+            ),
             any_comments: language.any_comments(),
             line_comments: language.line_comments(),
             multi_line_comments: language.multi_line_comments(),
@@ -114,8 +126,53 @@ impl SharedMatchers {
             nested_comments: language.nested_comments(),
             string_literals: language.quotes(),
             verbatim_string_literals: language.verbatim_quotes(),
+            complexity: init_corasick(language.complexity(), aho_corasick::StartKind::Both),
         }
     }
+
+    /// Whether `line` starts with a documentation comment. A comment that
+    /// goes on with the doc comment's last character, such as `////` or
+    /// `/***`, isn't one.
+    pub fn starts_with_doc_comment(&self, line: &[u8]) -> bool {
+        self.doc_comments.iter().any(|doc| {
+            line.starts_with(doc.as_bytes()) && line.get(doc.len()) != doc.as_bytes().last()
+        })
+    }
+
+    /// Counts the branches in a line of code without string literals or
+    /// multi-line comments, up to its line comment if it has one.
+    pub fn count_branches(&self, line: &[u8]) -> usize {
+        let code = self
+            .line_comments
+            .iter()
+            .filter_map(|c| line.windows(c.len()).position(|w| w == c.as_bytes()))
+            .min()
+            .map_or(line, |end| &line[..end]);
+
+        self.complexity
+            .find_iter(code)
+            .filter(|m| is_whole_word(code, m.start(), m.end()))
+            .count()
+    }
+
+    /// Returns the length of the branch keyword or operator that starts at
+    /// `start`, if there is one.
+    pub fn branch_at(&self, lines: &[u8], start: usize) -> Option<usize> {
+        let input = Input::new(lines).range(start..).anchored(Anchored::Yes);
+        self.complexity
+            .find(input)
+            .filter(|m| is_whole_word(lines, m.start(), m.end()))
+            .map(|m| m.len())
+    }
+}
+
+/// Whether `text[start..end]` isn't part of a longer identifier, so that
+/// keywords like `if` aren't found inside names like `notify`.
+fn is_whole_word(text: &[u8], start: usize, end: usize) -> bool {
+    let is_ident = |c: &u8| c.is_ascii_alphanumeric() || *c == b'_';
+    let joins_before = is_ident(&text[start]) && start > 0 && is_ident(&text[start - 1]);
+    let joins_after = is_ident(&text[end - 1]) && text.get(end).is_some_and(is_ident);
+    !joins_before && !joins_after
 }
 
 #[derive(Debug)]
@@ -132,8 +189,10 @@ impl SyntaxCounter {
             shared: SharedMatchers::new(language),
             quote_is_doc_quote: false,
             quote_is_verbatim: false,
+            comment_is_doc: false,
             stack: Vec::with_capacity(1),
             quote: None,
+            complexity: 0,
         }
     }
 
@@ -170,20 +229,20 @@ impl SyntaxCounter {
     }
 
     /// Try to see if we can determine what a line is from examining the whole
-    /// line at once. Returns `true` if successful.
+    /// line at once. Returns the kind of the line if successful.
     pub(crate) fn try_perform_single_line_analysis(
         &self,
         line: &[u8],
         stats: &mut crate::stats::CodeStats,
-    ) -> bool {
+    ) -> Option<LineKind> {
         if !self.is_plain_mode() {
-            false
+            None
         } else if line.trim().is_empty() {
             stats.blanks += 1;
             trace!("Blank No.{}", stats.blanks);
-            true
+            Some(LineKind::Blank)
         } else if self.shared.important_syntax.is_match(line) {
-            false
+            None
         } else {
             trace!("^ Skippable");
 
@@ -194,14 +253,21 @@ impl SyntaxCounter {
                     .iter()
                     .any(|c| line.starts_with(c.as_bytes()))
             {
-                stats.comments += 1;
-                trace!("Comment No.{}", stats.comments);
+                if self.shared.starts_with_doc_comment(line) {
+                    stats.docs += 1;
+                    trace!("Doc No.{}", stats.docs);
+                    Some(LineKind::Doc)
+                } else {
+                    stats.comments += 1;
+                    trace!("Comment No.{}", stats.comments);
+                    Some(LineKind::Comment)
+                }
             } else {
                 stats.code += 1;
+                stats.complexity += self.shared.count_branches(line);
                 trace!("Code No.{}", stats.code);
+                Some(LineKind::Code)
             }
-
-            true
         }
     }
 
@@ -264,11 +330,37 @@ impl SyntaxCounter {
                 ended_with_comments = true;
                 break;
             }
+
+            if self.stack.is_empty() {
+                if let Some(skip_amount) = self.shared.branch_at(lines, i) {
+                    self.complexity += 1;
+                    skip!(skip_amount);
+                    continue;
+                }
+            }
         }
 
         AnalysisReport::Normal(ended_with_comments)
     }
 
+    /// Whether a line that is a comment documents the code, because it starts
+    /// in a doc comment, starts one, or is part of a doc string that is
+    /// treated as a comment.
+    pub(crate) fn line_is_doc(&self, line: &[u8], config: &Config, started_in_docs: bool) -> bool {
+        let doc_strings = config.treat_doc_strings_as_comments == Some(true);
+        let trimmed = line.trim();
+
+        started_in_docs
+            || self.shared.starts_with_doc_comment(trimmed)
+            || (doc_strings && self.quote.is_some() && self.quote_is_doc_quote)
+            || (doc_strings
+                && self
+                    .shared
+                    .doc_quotes
+                    .iter()
+                    .any(|(start, _)| trimmed.starts_with(start.as_bytes())))
+    }
+
     /// Performs a set of heuristics to determine whether a line is a comment or
     /// not. The procedure is as follows.
     ///
@@ -609,6 +701,9 @@ impl SyntaxCounter {
                     || self.shared.allows_nested
                     || self.shared.nested_comments.contains(&(start, end))
                 {
+                    if self.stack.is_empty() {
+                        self.comment_is_doc = self.shared.starts_with_doc_comment(window);
+                    }
                     self.stack.push(end);
 
                     if log_enabled!(Trace) && self.shared.allows_nested {
@@ -49,14 +49,32 @@ extern crate serde;
 
 #[macro_use]
 mod utils;
+mod blame;
+mod cache;
+mod cancel;
+mod churn;
+mod classify;
 mod config;
+mod diff;
 mod language;
 mod sort;
 mod stats;
+mod tree;
 
 pub use self::{
-    config::Config,
-    language::{Language, LanguageType, Languages},
+    blame::{AuthorStats, Blame},
+    cache::{CacheEntry, FileStamp, ReportCache},
+    cancel::CancellationToken,
+    churn::{Churn, Hotspot, HotspotRank, Hotspots},
+    classify::FileClass,
+    config::{Config, ForEachDiscoveredFn, ForEachFn, LanguageConfig},
+    diff::{
+        CodeStatsDelta, FileDiff, LanguageChange, LanguageDiff, RevisionDiff, RevisionLanguageDiff,
+    },
+    language::{
+        Category, CustomLanguage, Language, LanguageDefinition, LanguageType, Languages,
+    },
     sort::Sort,
-    stats::{find_char_boundary, CodeStats, Report},
+    stats::{find_char_boundary, CodeStats, LineKind, Report},
+    tree::{DirectoryLanguage, DirectoryTree},
 };
@@ -5,9 +5,9 @@ mod cli;
 mod cli_utils;
 mod input;
 
-use std::{error::Error, io, process};
+use std::{cmp::Reverse, error::Error, io, process, sync::Arc};
 
-use tokei::{Config, Languages, Sort};
+use tokei::{Config, Languages, ReportCache, RevisionDiff, Sort};
 
 use crate::{
     cli::Cli,
@@ -23,7 +23,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         process::exit(0);
     }
 
-    let config = cli.override_config(Config::from_config_files());
+    let mut config = cli.override_config(Config::from_config_files());
+    config.register_custom_languages();
     let mut languages = Languages::new();
 
     if let Some(input) = cli.file_input() {
@@ -46,7 +47,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .columns
         .or(config.columns)
         .or_else(|| {
-            if cli.files {
+            if cli.files || cli.blame || cli.hotspots.is_some() {
                 term_size::dimensions().map(|(w, _)| w)
             } else {
                 None
@@ -57,30 +58,147 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     if cli.streaming == Some(crate::cli::Streaming::Simple) {
         println!(
-            "#{:^10} {:^80} {:^12} {:^12} {:^12} {:^12}",
-            "language", "path", "lines", "code", "comments", "blanks"
+            "#{:^10} {:^80} {:^12} {:^12} {:^12} {:^12} {:^12}",
+            "language", "path", "lines", "code", "comments", "docs", "blanks"
         );
         println!(
-            "{:>10} {:<80} {:>12} {:>12} {:>12} {:>12}",
+            "{:>10} {:<80} {:>12} {:>12} {:>12} {:>12} {:>12}",
             (0..10).map(|_| "#").collect::<String>(),
             (0..80).map(|_| "#").collect::<String>(),
             (0..12).map(|_| "#").collect::<String>(),
             (0..12).map(|_| "#").collect::<String>(),
             (0..12).map(|_| "#").collect::<String>(),
+            (0..12).map(|_| "#").collect::<String>(),
             (0..12).map(|_| "#").collect::<String>()
         );
     }
 
-    languages.get_statistics(&input, &cli.ignored_directories(), &config);
+    let cache_file = cli.cache_file();
+    if let Some(file) = &cache_file {
+        let cache = ReportCache::load(file, &config).with_content_hash(cli.cache_hash);
+        config.cache = Some(Arc::new(cache));
+    }
+
+    if let Some((from, to)) = &cli.diff {
+        let mut diff = RevisionDiff {
+            from: from.clone(),
+            to: to.clone(),
+            languages: Default::default(),
+        };
+        for path in &input {
+            match RevisionDiff::between(path, from, to, &cli.ignored_directories(), &config) {
+                Ok(other) => {
+                    for (language, changes) in other.languages {
+                        *diff.languages.entry(language).or_default() += changes;
+                    }
+                }
+                Err(error) => {
+                    eprintln!(
+                        "Error: couldn't compare '{}' between '{}' and '{}': {}",
+                        path,
+                        from,
+                        to,
+                        error.message()
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+
+        if let Some(format) = cli.output {
+            print!("{}", format.print_value(&diff).unwrap());
+            process::exit(0);
+        }
+
+        let mut printer = Printer::new(
+            columns,
+            cli.files,
+            io::BufWriter::new(io::stdout()),
+            cli.number_format,
+        );
+        if diff.languages.values().any(|changes| changes.inaccurate) {
+            printer.print_inaccuracy_warning()?;
+        }
+        printer.print_revision_diff(&diff)?;
+        return Ok(());
+    }
+
+    if let Some(revision) = &cli.revision {
+        for path in &input {
+            let result = languages.get_statistics_at_revision(
+                path,
+                revision,
+                &cli.ignored_directories(),
+                &config,
+            );
+            if let Err(error) = result {
+                eprintln!(
+                    "Error: couldn't read revision '{}' of '{}': {}",
+                    revision,
+                    path,
+                    error.message()
+                );
+                process::exit(1);
+            }
+        }
+    } else {
+        languages.get_statistics(&input, &cli.ignored_directories(), &config);
+    }
+
+    if let (Some(file), Some(cache)) = (&cache_file, &config.cache) {
+        if let Err(error) = cache.save(file) {
+            eprintln!(
+                "Error: couldn't write cache '{}': {}",
+                file.display(),
+                error
+            );
+        }
+    }
+
     if config.for_each_fn.is_some() {
         process::exit(0);
     }
 
+    let blame = cli.blame.then(|| languages.blame(&config));
+    if let Some(blame) = &blame {
+        if !blame.skipped.is_empty() {
+            eprintln!(
+                "Warning: {} files outside of a git repository, or that couldn't be blamed, \
+                were left out.",
+                blame.skipped.len()
+            );
+        }
+    }
+
+    let hotspots = cli.hotspots.map(|rank| {
+        let mut hotspots = languages.hotspots(&input, cli.since, cli.until, &config);
+        hotspots.sort_by(rank);
+        hotspots
+    });
+    if let Some(hotspots) = &hotspots {
+        if !hotspots.skipped.is_empty() {
+            eprintln!(
+                "Warning: {} files outside of a git repository were left out.",
+                hotspots.skipped.len()
+            );
+        }
+    }
+
     if let Some(format) = cli.output {
-        print!("{}", format.print(&languages).unwrap());
+        let output = match (&blame, &hotspots) {
+            (Some(blame), _) => format.print_value(blame),
+            (_, Some(hotspots)) => format.print_value(hotspots),
+            _ => format.print(&languages),
+        };
+        print!("{}", output.unwrap());
         process::exit(0);
     }
 
+    let directory_tree = cli
+        .by_dir
+        .filter(|_| hotspots.is_none())
+        .map(|depth| (languages.directory_tree(&input), depth));
+
     let mut printer = Printer::new(
         columns,
         cli.files,
@@ -92,6 +210,21 @@ fn main() -> Result<(), Box<dyn Error>> {
         printer.print_inaccuracy_warning()?;
     }
 
+    if let Some((tree, depth)) = directory_tree {
+        printer.print_directory_tree(&tree, depth)?;
+        return Ok(());
+    }
+
+    if let Some(blame) = blame {
+        printer.print_blame(&blame)?;
+        return Ok(());
+    }
+
+    if let Some(hotspots) = hotspots {
+        printer.print_hotspots(&hotspots, cli.by_dir)?;
+        return Ok(());
+    }
+
     printer.print_header()?;
 
     if let Some(sort_category) = cli.sort.or(config.sort) {
@@ -105,6 +238,8 @@ fn main() -> Result<(), Box<dyn Error>> {
             Sort::Blanks => languages.sort_by(|a, b| b.1.blanks.cmp(&a.1.blanks)),
             Sort::Comments => languages.sort_by(|a, b| b.1.comments.cmp(&a.1.comments)),
             Sort::Code => languages.sort_by(|a, b| b.1.code.cmp(&a.1.code)),
+            Sort::Docs => languages.sort_by_key(|(_, l)| Reverse(l.docs)),
+            Sort::Complexity => languages.sort_by_key(|(_, l)| Reverse(l.complexity)),
             Sort::Files => languages.sort_by(|a, b| b.1.reports.len().cmp(&a.1.reports.len())),
             Sort::Lines => languages.sort_by(|a, b| b.1.lines().cmp(&a.1.lines())),
         }
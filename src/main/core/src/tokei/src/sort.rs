@@ -1,6 +1,11 @@
-use std::{borrow::Cow, str::FromStr};
+use std::{borrow::Cow, cmp::Ordering, str::FromStr};
 
-use serde::de::{self, Deserialize, Deserializer};
+use serde::{
+    de::{self, Deserialize, Deserializer},
+    Serialize, Serializer,
+};
+
+use crate::stats::Report;
 
 /// Used for sorting languages.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -11,12 +16,45 @@ pub enum Sort {
     Comments,
     /// Sort by number code lines.
     Code,
+    /// Sort by number of documentation lines.
+    Docs,
+    /// Sort by number of branches in the code.
+    Complexity,
     /// Sort by number files lines.
     Files,
     /// Sort by number of lines.
     Lines,
 }
 
+impl Sort {
+    /// Orders two reports by this category, the biggest first except for
+    /// `Files` which orders them by name.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut small = Report::new("a.rs".into());
+    /// small.stats.code = 1;
+    /// let mut big = Report::new("b.rs".into());
+    /// big.stats.code = 10;
+    ///
+    /// let mut reports = vec![small, big];
+    /// reports.sort_by(|a, b| Sort::Code.cmp_reports(a, b));
+    /// assert_eq!(10, reports[0].stats.code);
+    /// ```
+    #[must_use]
+    pub fn cmp_reports(self, a: &Report, b: &Report) -> Ordering {
+        match self {
+            Sort::Blanks => b.stats.blanks.cmp(&a.stats.blanks),
+            Sort::Comments => b.stats.comments.cmp(&a.stats.comments),
+            Sort::Code => b.stats.code.cmp(&a.stats.code),
+            Sort::Docs => b.stats.docs.cmp(&a.stats.docs),
+            Sort::Complexity => b.stats.complexity.cmp(&a.stats.complexity),
+            Sort::Files => a.name.cmp(&b.name),
+            Sort::Lines => b.stats.lines().cmp(&a.stats.lines()),
+        }
+    }
+}
+
 impl FromStr for Sort {
     type Err = String;
 
@@ -27,6 +65,10 @@ impl FromStr for Sort {
             Sort::Comments
         } else if s.eq_ignore_ascii_case("code") {
             Sort::Code
+        } else if s.eq_ignore_ascii_case("docs") {
+            Sort::Docs
+        } else if s.eq_ignore_ascii_case("complexity") {
+            Sort::Complexity
         } else if s.eq_ignore_ascii_case("files") {
             Sort::Files
         } else if s.eq_ignore_ascii_case("lines") {
@@ -48,6 +90,23 @@ impl<'de> Deserialize<'de> for Sort {
     }
 }
 
+impl Serialize for Sort {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(match self {
+            Sort::Blanks => "blanks",
+            Sort::Comments => "comments",
+            Sort::Code => "code",
+            Sort::Docs => "docs",
+            Sort::Complexity => "complexity",
+            Sort::Files => "files",
+            Sort::Lines => "lines",
+        })
+    }
+}
+
 impl<'a> From<Sort> for Cow<'a, Sort> {
     fn from(from: Sort) -> Self {
         Cow::Owned(from)
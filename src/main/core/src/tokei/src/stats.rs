@@ -1,6 +1,6 @@
 use std::{collections::BTreeMap, fmt, ops, path::PathBuf};
 
-use crate::LanguageType;
+use crate::{FileClass, LanguageType};
 
 /// A struct representing stats about a single blob of code.
 #[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
@@ -10,8 +10,19 @@ pub struct CodeStats {
     pub blanks: usize,
     /// The lines of code in the blob.
     pub code: usize,
-    /// The lines of comments in the blob.
+    /// The lines of comments in the blob, not counting documentation.
     pub comments: usize,
+    /// The lines of documentation comments in the blob, such as `///` and
+    /// `/** */` in Rust, see [`LanguageType::doc_comments`]. Doc strings are
+    /// counted here when [`Config::treat_doc_strings_as_comments`] is set.
+    ///
+    /// [`Config::treat_doc_strings_as_comments`]: crate::Config::treat_doc_strings_as_comments
+    #[serde(default)]
+    pub docs: usize,
+    /// The branch keywords and operators in the code of the blob, such as
+    /// `if` and `&&`, see [`LanguageType::complexity`].
+    #[serde(default)]
+    pub complexity: usize,
     /// Language blobs that were contained inside this blob.
     pub blobs: BTreeMap<LanguageType, CodeStats>,
 }
@@ -26,7 +37,17 @@ impl CodeStats {
     /// Get the total lines in a blob of code.
     #[must_use]
     pub fn lines(&self) -> usize {
-        self.blanks + self.code + self.comments
+        self.blanks + self.code + self.comments + self.docs
+    }
+
+    /// Counts a single line of the given kind.
+    pub fn add_line(&mut self, kind: LineKind) {
+        match kind {
+            LineKind::Blank => self.blanks += 1,
+            LineKind::Code => self.code += 1,
+            LineKind::Comment => self.comments += 1,
+            LineKind::Doc => self.docs += 1,
+        }
     }
 
     /// Creates a new `CodeStats` from an existing one with all of the child
@@ -41,6 +62,8 @@ impl CodeStats {
             summary.blanks += child_summary.blanks;
             summary.comments += child_summary.comments;
             summary.code += child_summary.code;
+            summary.docs += child_summary.docs;
+            summary.complexity += child_summary.complexity;
         }
 
         summary
@@ -58,6 +81,8 @@ impl ops::AddAssign<&'_ CodeStats> for CodeStats {
         self.blanks += rhs.blanks;
         self.code += rhs.code;
         self.comments += rhs.comments;
+        self.docs += rhs.docs;
+        self.complexity += rhs.complexity;
 
         for (language, stats) in &rhs.blobs {
             *self.blobs.entry(*language).or_default() += stats;
@@ -65,6 +90,19 @@ impl ops::AddAssign<&'_ CodeStats> for CodeStats {
     }
 }
 
+/// What a single line of a file was counted as.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LineKind {
+    /// A blank line.
+    Blank,
+    /// A line of code.
+    Code,
+    /// A line of comments.
+    Comment,
+    /// A line of documentation comments.
+    Doc,
+}
+
 /// A struct representing the statistics of a file.
 #[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
@@ -73,6 +111,11 @@ pub struct Report {
     pub stats: CodeStats,
     /// File name.
     pub name: PathBuf,
+    /// Whether the file is generated or vendored, in which case it's kept in
+    /// [`Language::generated`](crate::Language::generated) rather than
+    /// counted with the other reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<FileClass>,
 }
 
 impl Report {
@@ -109,11 +152,12 @@ macro_rules! display_stats {
     ($f:expr, $this:expr, $name:expr, $max:expr) => {
         write!(
             $f,
-            " {: <max$} {:>12} {:>12} {:>12} {:>12}",
+            " {: <max$} {:>12} {:>12} {:>12} {:>12} {:>12}",
             $name,
             $this.stats.lines(),
             $this.stats.code,
             $this.stats.comments,
+            $this.stats.docs,
             $this.stats.blanks,
             max = $max
         )
@@ -0,0 +1,241 @@
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{CodeStats, LanguageType, Languages, Report};
+
+/// The statistics of one language under a directory.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct DirectoryLanguage {
+    /// The number of files of this language. Languages only found embedded
+    /// in other files have no files of their own.
+    pub files: usize,
+    /// The lines of this language, including the lines embedded in files of
+    /// other languages.
+    pub stats: CodeStats,
+}
+
+/// Statistics of every file under a directory, rolled up from the
+/// directories below it.
+///
+/// ```
+/// # use tokei::*;
+/// let mut rust = Language::new();
+/// let mut report = Report::new("src/utils/fs.rs".into());
+/// report.stats.code = 10;
+/// rust.add_report(report);
+/// let mut languages = Languages::new();
+/// languages.insert(LanguageType::Rust, rust);
+///
+/// let tree = languages.directory_tree::<&str>(&[]);
+/// assert_eq!(10, tree.get("src").unwrap().stats.code);
+/// assert_eq!(1, tree.get("src/utils").unwrap().files);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct DirectoryTree {
+    /// The number of files under this directory.
+    pub files: usize,
+    /// The combined lines of every file under this directory, embedded
+    /// languages included.
+    pub stats: CodeStats,
+    /// The lines under this directory broken down by language.
+    pub languages: BTreeMap<LanguageType, DirectoryLanguage>,
+    /// The directories directly below this one, by name.
+    pub children: BTreeMap<String, DirectoryTree>,
+}
+
+impl DirectoryTree {
+    /// Creates an empty tree.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to this directory and every directory between it and the
+    /// file, using the report's name as the path.
+    pub fn add_report(&mut self, language: LanguageType, report: &Report) {
+        self.add_report_at(&report.name, language, report);
+    }
+
+    fn add_report_at(&mut self, path: &Path, language: LanguageType, report: &Report) {
+        let mut own = report.stats.clone();
+        let blobs = std::mem::take(&mut own.blobs);
+        let blobs = blobs
+            .into_iter()
+            .map(|(ty, stats)| (ty, stats.summarise()))
+            .collect::<Vec<_>>();
+
+        let directories = path.parent().map_or_else(Vec::new, directory_names);
+        let mut node = self;
+        for name in std::iter::once(None).chain(directories.into_iter().map(Some)) {
+            if let Some(name) = name {
+                node = node.children.entry(name).or_default();
+            }
+            node.files += 1;
+            node.stats += &own;
+            let entry = node.languages.entry(language).or_default();
+            entry.files += 1;
+            entry.stats += &own;
+            for (ty, stats) in &blobs {
+                node.stats += stats;
+                node.languages.entry(*ty).or_default().stats += stats;
+            }
+        }
+    }
+
+    /// The directory at `path` relative to this one, if any file was found
+    /// under it.
+    #[must_use]
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&DirectoryTree> {
+        directory_names(path.as_ref())
+            .into_iter()
+            .try_fold(self, |node, name| node.children.get(&name))
+    }
+
+    /// Lists the directories below this one, parents before their children,
+    /// down to `max_depth` levels (every level when `None`). The paths are
+    /// relative to this directory.
+    #[must_use]
+    pub fn walk(&self, max_depth: Option<usize>) -> Vec<(PathBuf, &DirectoryTree)> {
+        let mut out = Vec::new();
+        self.walk_into(PathBuf::new(), 1, max_depth, &mut out);
+        out
+    }
+
+    fn walk_into<'a>(
+        &'a self,
+        path: PathBuf,
+        depth: usize,
+        max_depth: Option<usize>,
+        out: &mut Vec<(PathBuf, &'a DirectoryTree)>,
+    ) {
+        if max_depth.is_some_and(|max| depth > max) {
+            return;
+        }
+        for (name, child) in &self.children {
+            let path = path.join(name);
+            out.push((path.clone(), child));
+            child.walk_into(path, depth + 1, max_depth, out);
+        }
+    }
+}
+
+pub(crate) fn directory_names(path: &Path) -> Vec<String> {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect()
+}
+
+impl Languages {
+    /// Rolls the reports up into a tree of the directories they were found
+    /// in. Report paths are made relative to the first of `roots` they are
+    /// under. With more than one root, each root's own name is kept as the
+    /// top directory so that their trees stay apart.
+    #[must_use]
+    pub fn directory_tree<P: AsRef<Path>>(&self, roots: &[P]) -> DirectoryTree {
+        let mut tree = DirectoryTree::new();
+        for (ty, language) in self {
+            for report in &language.reports {
+                tree.add_report_at(relative_to_roots(&report.name, roots), *ty, report);
+            }
+        }
+        tree
+    }
+}
+
+/// `path` relative to the first of `roots` it is under, keeping each root's
+/// own name when there is more than one root.
+pub(crate) fn relative_to_roots<'a, P: AsRef<Path>>(path: &'a Path, roots: &[P]) -> &'a Path {
+    roots
+        .iter()
+        .map(AsRef::as_ref)
+        .find_map(|root| {
+            let base = if roots.len() > 1 {
+                root.parent().unwrap_or(root)
+            } else {
+                root
+            };
+            path.strip_prefix(base).ok()
+        })
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Language;
+
+    fn report(path: &str, code: usize) -> Report {
+        let mut report = Report::new(path.into());
+        report.stats.code = code;
+        report
+    }
+
+    fn languages() -> Languages {
+        let mut rust = Language::new();
+        rust.add_report(report("./proj/src/lib.rs", 10));
+        rust.add_report(report("./proj/src/cli/main.rs", 5));
+        let mut markdown = Language::new();
+        let mut readme = report("./proj/README.md", 2);
+        let mut embedded = CodeStats::new();
+        embedded.code = 3;
+        readme.stats.blobs.insert(LanguageType::Rust, embedded);
+        markdown.add_report(readme);
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::Markdown, markdown);
+        languages
+    }
+
+    #[test]
+    fn rolls_stats_up_every_directory() {
+        let tree = languages().directory_tree(&["./proj"]);
+
+        assert_eq!(3, tree.files);
+        assert_eq!(20, tree.stats.code);
+        assert_eq!(2, tree.get("src").unwrap().files);
+        assert_eq!(15, tree.get("src").unwrap().stats.code);
+        assert_eq!(5, tree.get("src/cli").unwrap().stats.code);
+        assert!(tree.get("docs").is_none());
+    }
+
+    #[test]
+    fn embedded_languages_count_under_their_own_type() {
+        let tree = languages().directory_tree(&["./proj"]);
+
+        let rust = &tree.languages[&LanguageType::Rust];
+        assert_eq!(2, rust.files);
+        assert_eq!(18, rust.stats.code);
+        let markdown = &tree.languages[&LanguageType::Markdown];
+        assert_eq!(1, markdown.files);
+        assert_eq!(2, markdown.stats.code);
+    }
+
+    #[test]
+    fn walk_respects_depth() {
+        let tree = languages().directory_tree(&["./proj"]);
+
+        let paths = |depth| {
+            tree.walk(depth)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![PathBuf::from("src")], paths(Some(1)));
+        assert_eq!(
+            vec![PathBuf::from("src"), PathBuf::from("src/cli")],
+            paths(None)
+        );
+    }
+
+    #[test]
+    fn several_roots_keep_their_names() {
+        let tree = languages().directory_tree(&["./proj/src", "./proj/docs"]);
+
+        assert_eq!(15, tree.get("src").unwrap().stats.code);
+        assert!(tree.get("src/cli").is_some());
+    }
+}
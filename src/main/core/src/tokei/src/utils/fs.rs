@@ -1,15 +1,24 @@
 use std::{collections::BTreeMap, path::Path};
 
-use ignore::{overrides::OverrideBuilder, DirEntry, WalkBuilder, WalkState::Continue};
+use ignore::{
+    overrides::OverrideBuilder,
+    DirEntry, WalkBuilder,
+    WalkState::{Continue, Quit},
+};
 
 use rayon::prelude::*;
 
 use crate::{
     config::Config,
     language::{Language, LanguageType},
+    utils::gitattributes::{Attributes, Linguist},
+    FileClass,
 };
 
-const IGNORE_FILE: &str = ".tokeignore";
+pub(crate) const IGNORE_FILE: &str = ".tokeignore";
+
+/// A file found by the walker, with what's known of its class before it's read.
+type Discovered = (DirEntry, LanguageType, Linguist, Option<FileClass>);
 
 pub fn get_all_files<A: AsRef<Path>>(
     paths: &[A],
@@ -18,6 +27,9 @@ pub fn get_all_files<A: AsRef<Path>>(
     config: &Config,
 ) {
     let languages = parking_lot::Mutex::new(languages);
+    if let Some(cache) = &config.cache {
+        cache.prepare(config);
+    }
     let (tx, rx) = crossbeam_channel::unbounded();
 
     let mut paths = paths.iter();
@@ -37,7 +49,11 @@ pub fn get_all_files<A: AsRef<Path>>(
         walker.overrides(overrides.build().expect("Excludes provided were invalid"));
     }
 
+    // dropped with the walk, so edits to `.gitattributes` are seen by the next
+    let attributes = &Attributes::default();
+
     let ignore = config.no_ignore.map(|b| !b).unwrap_or(true);
+    let skip_generated = config.skip_generated.unwrap_or(false);
     let ignore_dot = ignore && config.no_ignore_dot.map(|b| !b).unwrap_or(true);
     let ignore_vcs = ignore && config.no_ignore_vcs.map(|b| !b).unwrap_or(true);
 
@@ -58,6 +74,10 @@ pub fn get_all_files<A: AsRef<Path>>(
     walker.build_parallel().run(move || {
         let tx = tx.clone();
         Box::new(move |entry| {
+            if config.is_cancelled() {
+                return Quit;
+            }
+
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(error) => {
@@ -77,42 +97,84 @@ pub fn get_all_files<A: AsRef<Path>>(
                 }
             };
 
-            if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                tx.send(entry).unwrap();
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return Continue;
+            }
+
+            let linguist = attributes.of(entry.path());
+            if linguist.documentation {
+                return Continue;
             }
 
+            let class = FileClass::of_path(entry.path(), entry.depth());
+            if skip_generated && linguist.class(class).is_some() {
+                return Continue;
+            }
+
+            let language =
+                LanguageType::from_path_and_linguist(entry.path(), config, linguist.language);
+            let language = match language {
+                Some(language) => language,
+                None => return Continue,
+            };
+
+            if !config.is_counted(language) {
+                return Continue;
+            }
+
+            if let Some(f) = &config.for_each_discovered_fn {
+                f(language, entry.path());
+            }
+
+            tx.send((entry, language, linguist, class)).unwrap();
+
             Continue
         })
     });
 
-    let rx_iter = rx
-        .into_iter()
-        .par_bridge()
-        .filter_map(|e| LanguageType::from_path(e.path(), config).map(|l| (e, l)));
+    let process = |(entry, language, linguist, class): Discovered| {
+        if config.is_cancelled() {
+            return;
+        }
 
-    let process = |(entry, language): (DirEntry, LanguageType)| {
-        let result = language.parse(entry.into_path(), config);
-        let mut lock = languages.lock();
-        let entry = lock.entry(language).or_insert_with(Language::new);
+        let result = match &config.cache {
+            Some(cache) => cache.parse(language, entry.into_path(), config),
+            None => language.parse(entry.into_path(), config),
+        };
         match result {
-            Ok(stats) => {
-                let func = config.for_each_fn;
-                if let Some(f) = func {
+            Ok(mut stats) => {
+                stats.class = linguist.class(class.or(stats.class));
+                // checked before taking the entry, so a language whose files
+                // are all skipped doesn't get an empty one
+                if skip_generated && stats.class.is_some() {
+                    return;
+                }
+                if let Some(f) = &config.for_each_fn {
                     f(language, stats.clone())
                 };
-                entry.add_report(stats)
+                languages
+                    .lock()
+                    .entry(language)
+                    .or_insert_with(Language::new)
+                    .add_report(stats)
             }
             Err((error, path)) => {
-                entry.mark_inaccurate();
+                languages
+                    .lock()
+                    .entry(language)
+                    .or_insert_with(Language::new)
+                    .mark_inaccurate();
                 error!("Error reading {}:\n{}", path.display(), error);
             }
         }
     };
 
-    if let Some(types) = config.types.as_deref() {
-        rx_iter.filter(|(_, l)| types.contains(l)).for_each(process)
-    } else {
-        rx_iter.for_each(process)
+    rx.into_iter().par_bridge().for_each(process);
+
+    if let Some(cache) = &config.cache {
+        if !config.stopped_early() {
+            cache.retain_seen();
+        }
     }
 }
 
@@ -126,14 +188,22 @@ pub(crate) fn get_filename(path: &Path) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{
+        fs,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
     use tempfile::TempDir;
 
     use super::IGNORE_FILE;
     use crate::{
+        cancel::CancellationToken,
         config::Config,
         language::{languages::Languages, LanguageType},
+        FileClass,
     };
 
     const FILE_CONTENTS: &[u8] = &*b"fn main() {}";
@@ -452,6 +522,148 @@ mod tests {
         assert!(languages.get(LANGUAGE).is_some());
     }
 
+    #[test]
+    fn for_each_hooks_see_every_counted_file() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::write(dir.path().join(FILE_NAME), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("lib.rs"), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("unknown.extension"), FILE_CONTENTS).unwrap();
+
+        let discovered = Arc::new(AtomicUsize::new(0));
+        let parsed = Arc::new(AtomicUsize::new(0));
+        let counter = discovered.clone();
+        config.for_each_discovered_fn = Some(Arc::new(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        let counter = parsed.clone();
+        config.for_each_fn = Some(Arc::new(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert_eq!(discovered.load(Ordering::SeqCst), 2);
+        assert_eq!(parsed.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn cancelled_search_counts_nothing() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::write(dir.path().join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        let token = CancellationToken::new();
+        config.cancellation = Some(token.clone());
+        token.cancel();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert!(languages.get(LANGUAGE).is_none());
+        assert!(token.stopped_early());
+    }
+
+    #[test]
+    fn search_cancelled_after_it_finished_is_complete() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::write(dir.path().join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        let token = CancellationToken::new();
+        config.cancellation = Some(token.clone());
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+        token.cancel();
+
+        assert!(languages.get(LANGUAGE).is_some());
+        assert!(token.is_cancelled());
+        assert!(!token.stopped_early());
+    }
+
+    #[test]
+    fn generated_and_vendored_files_are_kept_apart() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let mut config = Config::default();
+        let mut languages = Languages::new();
+
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join(FILE_NAME), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("vendor").join(FILE_NAME), FILE_CONTENTS).unwrap();
+        fs::write(dir.path().join("api.rs"), "// @generated\nfn api() {}\n").unwrap();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        let rust = &languages[LANGUAGE];
+        assert_eq!(rust.reports.len(), 1);
+        let mut classes = rust.generated.iter().map(|r| r.class).collect::<Vec<_>>();
+        classes.sort_by_key(|class| format!("{:?}", class));
+        assert_eq!(
+            classes,
+            [Some(FileClass::Generated), Some(FileClass::Vendored)]
+        );
+
+        config.skip_generated = Some(true);
+        let mut languages = Languages::new();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert_eq!(languages[LANGUAGE].reports.len(), 1);
+        assert!(languages[LANGUAGE].generated.is_empty());
+    }
+
+    #[test]
+    fn skipped_generated_files_leave_no_empty_language() {
+        let dir = TempDir::new().expect("Couldn't create temp dir.");
+        let config = Config {
+            skip_generated: Some(true),
+            ..Config::default()
+        };
+        let mut languages = Languages::new();
+
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor").join(FILE_NAME), FILE_CONTENTS).unwrap();
+
+        super::get_all_files(
+            &[dir.path().to_str().unwrap()],
+            &[],
+            &mut languages,
+            &config,
+        );
+
+        assert!(languages.get(LANGUAGE).is_none());
+    }
+
     #[test]
     fn custom_ignore() {
         let dir = TempDir::new().expect("Couldn't create temp dir.");
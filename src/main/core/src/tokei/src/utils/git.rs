@@ -0,0 +1,948 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    io::{self, Read},
+    iter,
+    path::{Path, PathBuf},
+};
+
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use git2::{BlameOptions, DiffFindOptions, DiffOptions, ObjectType, Oid, Patch, Repository, Tree};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    overrides::{Override, OverrideBuilder},
+    Match,
+};
+use rayon::prelude::*;
+
+use crate::{
+    blame::{AuthorStats, Blame},
+    config::Config,
+    diff::RevisionDiff,
+    language::{Language, LanguageType},
+    stats::{CodeStats, Report},
+    utils::fs::IGNORE_FILE,
+    FileClass,
+};
+
+/// Mode git gives symbolic links, which are stored as blobs of their target.
+const SYMLINK_MODE: i32 = 0o120000;
+
+/// Counts the files below `path`, a directory of a git repository, as they
+/// are in `revision`, reading them from the object database instead of the
+/// working tree. Ignore files are taken from the same revision, so a file is
+/// counted if `get_all_files` would count it in a checkout of `revision`.
+pub fn get_all_files_at_revision(
+    path: &Path,
+    revision: &str,
+    ignored_directories: &[&str],
+    languages: &mut BTreeMap<LanguageType, Language>,
+    config: &Config,
+) -> Result<(), git2::Error> {
+    let checkout = Checkout::open(path)?;
+    let files = checkout.files(revision, ignored_directories, config)?;
+
+    let git_dir = checkout.repo.path().to_path_buf();
+    let counted = checkout.path;
+    let skip_generated = config.skip_generated.unwrap_or(false);
+    let languages = parking_lot::Mutex::new(languages);
+    files.into_par_iter().for_each_init(
+        || Repository::open(&git_dir),
+        |repo, (path, oid, language)| {
+            if config.is_cancelled() {
+                return;
+            }
+
+            let result = read_blob(repo, oid);
+            let mut lock = languages.lock();
+            let entry = lock.entry(language).or_insert_with(Language::new);
+            match result {
+                Ok(text) => {
+                    let depth = path
+                        .strip_prefix(counted)
+                        .map_or(1, |relative| relative.components().count());
+                    let mut report = Report::new(path);
+                    report += language.parse_from_slice(&text, config);
+                    report.class = FileClass::of_path(&report.name, depth)
+                        .or_else(|| FileClass::of_content(language, &text));
+                    if skip_generated && report.class.is_some() {
+                        return;
+                    }
+                    if let Some(f) = &config.for_each_fn {
+                        f(language, report.clone())
+                    };
+                    entry.add_report(report)
+                }
+                Err(error) => {
+                    entry.mark_inaccurate();
+                    error!("Error reading {}:\n{}", path.display(), error);
+                }
+            }
+        },
+    );
+
+    Ok(())
+}
+
+/// Compares the files below `path`, a directory of a git repository, between
+/// the revisions `from` and `to`. Files are selected like
+/// [`get_all_files_at_revision`] does, and only those whose contents or
+/// language differ are parsed.
+pub fn diff_revisions(
+    path: &Path,
+    from: &str,
+    to: &str,
+    ignored_directories: &[&str],
+    config: &Config,
+) -> Result<RevisionDiff, git2::Error> {
+    let checkout = Checkout::open(path)?;
+    let files_by_path = |revision| -> Result<BTreeMap<_, _>, git2::Error> {
+        Ok(checkout
+            .files(revision, ignored_directories, config)?
+            .into_iter()
+            .map(|(path, oid, language)| (path, (oid, language)))
+            .collect())
+    };
+    let older = files_by_path(from)?;
+    let mut newer = files_by_path(to)?;
+
+    let mut changed = Vec::new();
+    for (path, old) in older {
+        match newer.remove(&path) {
+            Some(new) if new == old => {}
+            new => changed.push((path, Some(old), new)),
+        }
+    }
+    changed.extend(newer.into_iter().map(|(path, new)| (path, None, Some(new))));
+
+    let git_dir = checkout.repo.path().to_path_buf();
+    let parsed = changed
+        .into_par_iter()
+        .map_init(
+            || Repository::open(&git_dir),
+            |repo, (path, old, new)| {
+                let parse = |side: Option<(Oid, LanguageType)>| {
+                    side.map(|(oid, language)| {
+                        let stats = read_blob(repo, oid)
+                            .map(|text| language.parse_from_slice(text, config).summarise());
+                        (language, stats)
+                    })
+                };
+                let old = parse(old);
+                let new = parse(new);
+                (path, old, new)
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let mut diff = RevisionDiff {
+        from: from.to_owned(),
+        to: to.to_owned(),
+        languages: BTreeMap::new(),
+    };
+    for (path, old, new) in parsed {
+        let mut stats = |side: Option<(LanguageType, io::Result<CodeStats>)>| {
+            let (language, stats) = side?;
+            match stats {
+                Ok(stats) => Some((language, stats)),
+                Err(error) => {
+                    diff.languages.entry(language).or_default().inaccurate = true;
+                    error!("Error reading {}:\n{}", path.display(), error);
+                    None
+                }
+            }
+        };
+        let old = stats(old);
+        let new = stats(new);
+        diff.add_file(path, old, new);
+    }
+
+    Ok(diff)
+}
+
+/// Splits the lines of each of `files`, as they are in the working tree, by
+/// the author who changed them last. Files outside of a repository, or that
+/// can't be read, are skipped.
+pub fn blame_files(files: Vec<(PathBuf, LanguageType)>, config: &Config) -> Blame {
+    let mut blame = Blame::default();
+    let (found, outside) = locate(files);
+    for path in outside {
+        blame.skip(&path);
+    }
+
+    let blamed = found
+        .into_par_iter()
+        .map_init(HashMap::new, |repos, (root, relative, path, language)| {
+            if config.is_cancelled() {
+                return (path, language, None);
+            }
+
+            let repo = repos
+                .entry(root)
+                .or_insert_with_key(|root| Repository::open(root));
+            let authors = match repo {
+                Ok(repo) => blame_file(repo, &relative, &path, language, config),
+                Err(error) => Err(git2::Error::from_str(error.message())),
+            };
+            (path, language, Some(authors))
+        })
+        .collect::<Vec<_>>();
+
+    for (path, language, authors) in blamed {
+        match authors {
+            Some(Ok(authors)) => blame.add_file(language, authors),
+            Some(Err(error)) => {
+                warn!("Couldn't blame {}:\n{}", path.display(), error.message());
+                blame.skip(&path);
+            }
+            None => {}
+        }
+    }
+
+    blame.sort();
+    blame
+}
+
+/// The changes of one commit to the files it touched, as `(file, added,
+/// removed)`.
+type CommitChanges = Vec<(usize, usize, usize)>;
+
+/// The lines that each commit made within `since..until`, in seconds since
+/// the Unix epoch, added to and removed from the working tree files `files`,
+/// as `(index into files, added, removed)`. Every commit in the history of
+/// `HEAD` is read except merges, and renames are followed so that changes
+/// made under an older name count for the file's current name. The indices
+/// of files outside of a repository are returned apart.
+pub fn file_changes(
+    files: &[PathBuf],
+    since: Option<i64>,
+    until: Option<i64>,
+    config: &Config,
+) -> (Vec<CommitChanges>, Vec<usize>) {
+    let indexed = files
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(index, path)| (path, index))
+        .collect();
+    let (found, _) = locate(indexed);
+
+    let mut by_root = BTreeMap::<_, HashMap<_, _>>::new();
+    let mut outside = (0..files.len()).collect::<BTreeSet<_>>();
+    for (root, relative, _, index) in found {
+        outside.remove(&index);
+        by_root.entry(root).or_default().insert(relative, index);
+    }
+
+    let mut commits = Vec::new();
+    for (root, tracked) in by_root {
+        let history = Repository::open(&root)
+            .and_then(|repo| history_changes(&repo, &tracked, since, until, config));
+        match history {
+            Ok(history) => commits.extend(history),
+            Err(error) => {
+                warn!(
+                    "Couldn't read the history of {}:\n{}",
+                    root.display(),
+                    error.message()
+                );
+                outside.extend(tracked.into_values());
+            }
+        }
+    }
+
+    (commits, outside.into_iter().collect())
+}
+
+/// The changes of each commit of `repo` to the `tracked` files, keyed by
+/// their path from the root of the working tree, see [`file_changes`].
+fn history_changes(
+    repo: &Repository,
+    tracked: &HashMap<PathBuf, usize>,
+    since: Option<i64>,
+    until: Option<i64>,
+    config: &Config,
+) -> Result<Vec<CommitChanges>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    if walk.push_head().is_err() {
+        // Nothing is committed yet.
+        return Ok(Vec::new());
+    }
+    walk.set_sorting(git2::Sort::TIME)?;
+
+    // The current name of files that were renamed by the commits seen so
+    // far, newest first.
+    let mut renames = HashMap::<PathBuf, PathBuf>::new();
+    let mut find = DiffFindOptions::new();
+    find.renames(true);
+    let mut commits = Vec::new();
+    for oid in walk {
+        if config.is_cancelled() {
+            break;
+        }
+
+        let commit = repo.find_commit(oid?)?;
+        let time = commit.time().seconds();
+        if since.is_some_and(|since| time < since) {
+            break;
+        }
+        let parent = match commit.parent_count() {
+            0 => None,
+            1 => Some(commit.parent(0)?.tree()?),
+            _ => continue,
+        };
+
+        let mut diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), None)?;
+        diff.find_similar(Some(&mut find))?;
+        // Commits after the window are still read for their renames.
+        let counted = until.is_none_or(|until| time < until);
+        let mut changes = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let path = match delta.new_file().path() {
+                Some(path) => path,
+                None => continue,
+            };
+            let current = renames
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| path.to_owned());
+            if delta.status() == git2::Delta::Renamed {
+                if let Some(old) = delta.old_file().path() {
+                    renames.insert(old.to_owned(), current.clone());
+                }
+            }
+
+            let file = match tracked.get(&current) {
+                Some(&file) if counted => file,
+                _ => continue,
+            };
+            // Binary files only count their commits.
+            let (_, added, removed) = match Patch::from_diff(&diff, index)? {
+                Some(patch) => patch.line_stats()?,
+                None => (0, 0, 0),
+            };
+            changes.push((file, added, removed));
+        }
+
+        if !changes.is_empty() {
+            commits.push(changes);
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Finds the repository of each of `files`, giving the root of its working
+/// tree and its path from there. Repositories are found once per directory.
+/// Files outside of a repository are returned apart.
+#[allow(clippy::type_complexity)]
+fn locate<T>(files: Vec<(PathBuf, T)>) -> (Vec<(PathBuf, PathBuf, PathBuf, T)>, Vec<PathBuf>) {
+    let mut roots = HashMap::new();
+    let mut found = Vec::new();
+    let mut outside = Vec::new();
+    for (path, value) in files {
+        let dir = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+        let root = roots.entry(dir).or_insert_with_key(|dir| {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            let repo = Repository::discover(dir).ok()?;
+            repo.workdir()?.canonicalize().ok()
+        });
+        let relative = root.as_ref().and_then(|root| {
+            let path = path.canonicalize().ok()?;
+            path.strip_prefix(root).map(Path::to_path_buf).ok()
+        });
+        match (root, relative) {
+            (Some(root), Some(relative)) => found.push((root.clone(), relative, path, value)),
+            _ => outside.push(path),
+        }
+    }
+    (found, outside)
+}
+
+/// The lines of the file at `path`, found at `relative` in `repo`, counted
+/// by the name and email of their author.
+fn blame_file(
+    repo: &Repository,
+    relative: &Path,
+    path: &Path,
+    language: LanguageType,
+    config: &Config,
+) -> Result<BTreeMap<(String, String), CodeStats>, git2::Error> {
+    let to_git = |e: io::Error| git2::Error::from_str(&e.to_string());
+    let contents = fs::read(path).map_err(to_git)?;
+    let mut text = Vec::new();
+    DecodeReaderBytesBuilder::new()
+        .build(&*contents)
+        .read_to_end(&mut text)
+        .map_err(to_git)?;
+
+    // Files that aren't in `HEAD` have no committed lines.
+    let committed = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .and_then(|tree| tree.get_path(relative))
+        .ok();
+
+    let mut owners = Vec::new();
+    let mut hunks = Vec::new();
+    if let Some(entry) = committed {
+        let mut options = BlameOptions::new();
+        options.use_mailmap(true);
+        for hunk in repo.blame_file(relative, Some(&mut options))?.iter() {
+            let signature = hunk.final_signature();
+            let author = (
+                String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+                String::from_utf8_lossy(signature.email_bytes()).into_owned(),
+            );
+            owners.extend(iter::repeat_n(author, hunk.lines_in_hunk()));
+        }
+
+        let blob = repo.find_blob(entry.id())?;
+        if blob.content() != &contents[..] {
+            let mut options = DiffOptions::new();
+            options.context_lines(0);
+            let patch =
+                Patch::from_blob_and_buffer(&blob, None, &contents, None, Some(&mut options))?;
+            for index in 0..patch.num_hunks() {
+                let (hunk, _) = patch.hunk(index)?;
+                hunks.push((
+                    hunk.old_lines() as usize,
+                    hunk.new_start() as usize,
+                    hunk.new_lines() as usize,
+                ));
+            }
+        }
+    }
+
+    let not_committed = (AuthorStats::NOT_COMMITTED.to_owned(), String::new());
+    let kinds = language.line_kinds(&text, config);
+    let mut authors = BTreeMap::<_, CodeStats>::new();
+    for (line, kind) in committed_lines(&hunks, kinds.len()).into_iter().zip(kinds) {
+        let author = line
+            .and_then(|line| owners.get(line))
+            .unwrap_or(&not_committed);
+        authors.entry(author).or_default().add_line(kind);
+    }
+
+    Ok(authors
+        .into_iter()
+        .map(|(author, stats)| (author.clone(), stats))
+        .collect())
+}
+
+/// The zero-based line of the committed file that each of the first `lines`
+/// lines of the working file was, given the hunks of a diff between them
+/// without context as `(old_lines, new_start, new_lines)`. Lines added since
+/// have none.
+fn committed_lines(hunks: &[(usize, usize, usize)], lines: usize) -> Vec<Option<usize>> {
+    let mut hunks = hunks.iter().peekable();
+    let mut offset = 0;
+    (1..=lines)
+        .map(|line| {
+            // A hunk that only removes lines starts after the line `new_start`.
+            while let Some(&&(old_lines, new_start, new_lines)) = hunks.peek() {
+                if line < new_start + new_lines.max(1) {
+                    break;
+                }
+                offset += old_lines as isize - new_lines as isize;
+                hunks.next();
+            }
+            match hunks.peek() {
+                Some(&&(_, new_start, new_lines)) if new_lines > 0 && line >= new_start => None,
+                _ => Some((line as isize + offset - 1) as usize),
+            }
+        })
+        .collect()
+}
+
+/// A directory of a git repository whose revisions are counted.
+struct Checkout<'a> {
+    repo: Repository,
+    /// Where the repository is, or would be, checked out.
+    root: PathBuf,
+    /// The directory being counted, and the same directory relative to
+    /// `root`. Reports are named after `path`.
+    path: &'a Path,
+    prefix: PathBuf,
+}
+
+impl<'a> Checkout<'a> {
+    fn open(path: &'a Path) -> Result<Self, git2::Error> {
+        let repo = Repository::discover(path)?;
+        let root = repo.workdir().unwrap_or(path).to_path_buf();
+        let prefix = match (path.canonicalize(), root.canonicalize()) {
+            (Ok(path), Ok(root)) => path.strip_prefix(root).map(Path::to_path_buf).ok(),
+            _ => None,
+        }
+        .unwrap_or_default();
+
+        Ok(Self {
+            repo,
+            root,
+            path,
+            prefix,
+        })
+    }
+
+    /// The files to count in `revision`, with their blob and language.
+    fn files(
+        &self,
+        revision: &str,
+        ignored_directories: &[&str],
+        config: &Config,
+    ) -> Result<Vec<(PathBuf, Oid, LanguageType)>, git2::Error> {
+        let tree = self.repo.revparse_single(revision)?.peel_to_tree()?;
+
+        let mut overrides = OverrideBuilder::new(self.path);
+        for ignored in ignored_directories {
+            rs_error!(overrides.add(&format!("!{}", ignored)));
+        }
+
+        let ignore = config.no_ignore.map(|b| !b).unwrap_or(true);
+        let walker = RevisionWalker {
+            checkout: self,
+            config,
+            excludes: overrides.build().expect("Excludes provided were invalid"),
+            ignore_dot: ignore && config.no_ignore_dot.map(|b| !b).unwrap_or(true),
+            ignore_vcs: ignore && config.no_ignore_vcs.map(|b| !b).unwrap_or(true),
+        };
+
+        let mut stack = Vec::new();
+        if walker.ignore_vcs {
+            let mut exclude = GitignoreBuilder::new(&self.root);
+            exclude.add(self.repo.path().join("info").join("exclude"));
+            stack.push(exclude.build().into_iter().collect());
+        }
+
+        let mut files = Vec::new();
+        walker.walk(&tree, Path::new(""), &mut stack, &mut files)?;
+        Ok(files)
+    }
+}
+
+/// The decoded contents of a blob, read like files on disk are.
+fn read_blob(repo: &Result<Repository, git2::Error>, oid: Oid) -> io::Result<Vec<u8>> {
+    let to_io = |e: &git2::Error| io::Error::other(e.message().to_owned());
+    let blob = repo
+        .as_ref()
+        .map_err(to_io)?
+        .find_blob(oid)
+        .map_err(|e| to_io(&e))?;
+    let mut text = Vec::new();
+    DecodeReaderBytesBuilder::new()
+        .build(blob.content())
+        .read_to_end(&mut text)?;
+    Ok(text)
+}
+
+struct RevisionWalker<'a> {
+    checkout: &'a Checkout<'a>,
+    config: &'a Config,
+    excludes: Override,
+    ignore_dot: bool,
+    ignore_vcs: bool,
+}
+
+impl RevisionWalker<'_> {
+    /// Collects the files to count below `tree`, the directory `dir` of the
+    /// revision. `stack` holds the ignore files of every parent directory.
+    fn walk(
+        &self,
+        tree: &Tree,
+        dir: &Path,
+        stack: &mut Vec<Vec<Gitignore>>,
+        files: &mut Vec<(PathBuf, Oid, LanguageType)>,
+    ) -> Result<(), git2::Error> {
+        if self.config.is_cancelled() {
+            return Ok(());
+        }
+
+        stack.push(self.ignore_files(tree, dir)?);
+
+        for entry in tree.iter() {
+            let name = match entry.name() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if !self.config.hidden.unwrap_or(false) && name.starts_with('.') {
+                continue;
+            }
+
+            let relative = dir.join(name);
+            let is_dir = entry.kind() == Some(ObjectType::Tree);
+            // Directories above the counted one are only visited for their
+            // ignore files.
+            let path = match relative.strip_prefix(&self.checkout.prefix) {
+                Ok(below) => self.checkout.path.join(below),
+                Err(_) if is_dir && self.checkout.prefix.starts_with(&relative) => {
+                    let subtree = self.checkout.repo.find_tree(entry.id())?;
+                    self.walk(&subtree, &relative, stack, files)?;
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let checkout_path = self.checkout.root.join(&relative);
+            if self.excludes.matched(&path, is_dir).is_ignore()
+                || is_ignored(stack, &checkout_path, is_dir)
+            {
+                continue;
+            }
+
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    let subtree = self.checkout.repo.find_tree(entry.id())?;
+                    self.walk(&subtree, &relative, stack, files)?;
+                }
+                Some(ObjectType::Blob) if entry.filemode() != SYMLINK_MODE => {
+                    // The blob is only loaded when the path doesn't settle
+                    // the language, as a file in the working tree is only read.
+                    let mut missing = None;
+                    let language = LanguageType::from_path_and_loaded_content(
+                        &path,
+                        self.config,
+                        || match self.checkout.repo.find_blob(entry.id()) {
+                            Ok(blob) => Some(blob.content().to_vec()),
+                            Err(error) => {
+                                missing = Some(error);
+                                None
+                            }
+                        },
+                    );
+                    if let Some(error) = missing {
+                        return Err(error);
+                    }
+
+                    let language = match language {
+                        Some(language) => language,
+                        None => continue,
+                    };
+
+                    if !self.config.is_counted(language) {
+                        continue;
+                    }
+
+                    if let Some(f) = &self.config.for_each_discovered_fn {
+                        f(language, &path);
+                    }
+
+                    files.push((path, entry.id(), language));
+                }
+                // Submodules and symbolic links aren't followed.
+                _ => {}
+            }
+        }
+
+        stack.pop();
+        Ok(())
+    }
+
+    /// The ignore files of a directory, most significant first.
+    fn ignore_files(&self, tree: &Tree, dir: &Path) -> Result<Vec<Gitignore>, git2::Error> {
+        let mut names = Vec::new();
+        if self.ignore_dot {
+            names.extend([IGNORE_FILE, ".ignore"]);
+        }
+        if self.ignore_vcs {
+            names.push(".gitignore");
+        }
+
+        let mut ignores = Vec::new();
+        for name in names {
+            let entry = match tree.get_name(name) {
+                Some(entry) if entry.kind() == Some(ObjectType::Blob) => entry,
+                _ => continue,
+            };
+            let blob = self.checkout.repo.find_blob(entry.id())?;
+            let mut builder = GitignoreBuilder::new(self.checkout.root.join(dir));
+            for line in String::from_utf8_lossy(blob.content()).lines() {
+                rs_error!(builder.add_line(None, line));
+            }
+            match builder.build() {
+                Ok(gitignore) => ignores.push(gitignore),
+                Err(error) => error!("{} in {}", error, dir.join(name).display()),
+            }
+        }
+
+        Ok(ignores)
+    }
+}
+
+/// Whether the closest ignore file with a rule for `path` ignores it.
+fn is_ignored(stack: &[Vec<Gitignore>], path: &Path, is_dir: bool) -> bool {
+    for ignore in stack.iter().rev().flatten() {
+        match ignore.matched(path, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    use crate::{
+        config::Config,
+        language::{languages::Languages, LanguageType},
+    };
+
+    fn commit_all(repo: &Repository, message: &str) {
+        commit_as(repo, "tokei", message);
+    }
+
+    fn commit_as(repo: &Repository, author: &str, message: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::FORCE, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now(author, &format!("{}@example.com", author)).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    fn code_at(dir: &TempDir, revision: &str, config: &Config) -> Languages {
+        let mut languages = Languages::new();
+        languages
+            .get_statistics_at_revision(dir.path(), revision, &[], config)
+            .unwrap();
+        languages
+    }
+
+    #[test]
+    fn counts_older_revisions_without_checkout() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        commit_all(&repo, "first");
+        fs::write(dir.path().join("main.rs"), "fn main() {}\nfn more() {}\n").unwrap();
+        fs::write(dir.path().join("run"), "#!/bin/sh\necho hi\n").unwrap();
+        commit_all(&repo, "second");
+        fs::remove_file(dir.path().join("main.rs")).unwrap();
+
+        let head = code_at(&dir, "HEAD", &Config::default());
+        assert_eq!(head[&LanguageType::Rust].code, 2);
+        assert_eq!(head[&LanguageType::Sh].code, 1);
+        assert_eq!(
+            head[&LanguageType::Rust].reports[0].name,
+            dir.path().join("main.rs")
+        );
+
+        let first = code_at(&dir, "HEAD~1", &Config::default());
+        assert_eq!(first[&LanguageType::Rust].code, 1);
+        assert!(first.get(&LanguageType::Sh).is_none());
+    }
+
+    #[test]
+    fn ambiguous_extensions_are_recognised_by_content() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("a.h"), "@interface A : NSObject\n@end\n").unwrap();
+        commit_all(&repo, "objective-c header");
+        fs::write(dir.path().join("a.h"), "int a(void);\n").unwrap();
+
+        let head = code_at(&dir, "HEAD", &Config::default());
+
+        assert_eq!(head[&LanguageType::ObjectiveC].code, 2);
+        assert!(head.get(&LanguageType::CHeader).is_none());
+    }
+
+    #[test]
+    fn honors_ignore_files_of_the_revision() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("a.gen.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.path().join("keep.gen.rs"), "fn keep() {}\n").unwrap();
+        fs::write(dir.path().join("target/dep.rs"), "fn dep() {}\n").unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.gen.rs\ntarget/\n").unwrap();
+        fs::write(dir.path().join(".tokeignore"), "!keep.gen.rs\n").unwrap();
+        commit_all(&repo, "ignored files are tracked too");
+
+        let rust = &code_at(&dir, "HEAD", &Config::default())[&LanguageType::Rust];
+        let mut names = rust
+            .reports
+            .iter()
+            .map(|r| r.name.strip_prefix(dir.path()).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            ["keep.gen.rs", "main.rs"].map(std::path::PathBuf::from)
+        );
+
+        let config = Config {
+            no_ignore: Some(true),
+            ..Config::default()
+        };
+        assert_eq!(code_at(&dir, "HEAD", &config)[&LanguageType::Rust].code, 4);
+    }
+
+    #[test]
+    fn counts_a_directory_of_the_repository() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::create_dir_all(dir.path().join("src/bin")).unwrap();
+        fs::write(dir.path().join("build.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "fn lib() {}\n").unwrap();
+        fs::write(dir.path().join("src/bin/tool.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join(".gitignore"), "src/bin/\n").unwrap();
+        commit_all(&repo, "nested");
+
+        let src = dir.path().join("src");
+        let mut languages = Languages::new();
+        languages
+            .get_statistics_at_revision(&src, "HEAD", &[], &Config::default())
+            .unwrap();
+
+        let rust = &languages[&LanguageType::Rust];
+        assert_eq!(rust.code, 1);
+        assert_eq!(rust.reports[0].name, src.join("lib.rs"));
+    }
+
+    #[test]
+    fn diff_only_covers_changed_files() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("same.rs"), "fn same() {}\n").unwrap();
+        fs::write(dir.path().join("lib.rs"), "// lib\nfn lib() {}\n").unwrap();
+        fs::write(dir.path().join("gone.c"), "int x;\n").unwrap();
+        commit_all(&repo, "first");
+        fs::write(dir.path().join("lib.rs"), "fn lib() {}\n\nfn more() {}\n").unwrap();
+        fs::remove_file(dir.path().join("gone.c")).unwrap();
+        fs::write(dir.path().join("new.rs"), "fn new() {}\n").unwrap();
+        commit_all(&repo, "second");
+
+        let diff =
+            crate::RevisionDiff::between(dir.path(), "HEAD~1", "HEAD", &[], &Config::default())
+                .unwrap();
+
+        let rust = &diff.languages[&LanguageType::Rust];
+        assert_eq!((rust.added.code, rust.added.blanks), (2, 1));
+        assert_eq!(rust.removed.comments, 1);
+        let mut names = rust
+            .files
+            .iter()
+            .map(|f| f.name.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["lib.rs", "new.rs"]);
+        assert_eq!(diff.languages[&LanguageType::C].removed.code, 1);
+    }
+
+    #[test]
+    fn blame_splits_lines_by_author() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let main = dir.path().join("main.rs");
+        fs::write(&main, "// main\nfn main() {}\n").unwrap();
+        commit_as(&repo, "alice", "first");
+        fs::write(&main, "// main\nfn main() {}\n\nfn bob() {}\n").unwrap();
+        commit_as(&repo, "bob", "second");
+        fs::write(
+            &main,
+            "// main\nfn main() {}\n\nfn bob() {}\nfn mine() {}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("new.c"), "int x;\n").unwrap();
+
+        let config = Config::default();
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], &config);
+        let blame = languages.blame(&config);
+
+        let rust = blame.languages[&LanguageType::Rust]
+            .iter()
+            .map(|a| {
+                (
+                    a.name.as_str(),
+                    a.stats.code,
+                    a.stats.comments,
+                    a.stats.blanks,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rust,
+            [
+                ("alice", 1, 1, 0),
+                ("bob", 1, 0, 1),
+                (crate::AuthorStats::NOT_COMMITTED, 1, 0, 0)
+            ]
+        );
+        assert!(blame.languages[&LanguageType::C][0].is_not_committed());
+        assert!(blame.skipped.is_empty());
+        let authors = blame
+            .authors()
+            .into_iter()
+            .map(|a| (a.name, a.files, a.stats.lines()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            authors,
+            [
+                (crate::AuthorStats::NOT_COMMITTED.to_owned(), 2, 2),
+                ("alice".to_owned(), 1, 2),
+                ("bob".to_owned(), 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn blame_skips_files_outside_repositories() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let config = Config::default();
+        let mut languages = Languages::new();
+        languages.get_statistics(&[dir.path()], &[], &config);
+        let blame = languages.blame(&config);
+
+        assert!(blame.languages.is_empty());
+        assert_eq!(blame.skipped, [dir.path().join("main.rs")]);
+    }
+
+    #[test]
+    fn committed_lines_skip_changed_hunks() {
+        // Line 2 was replaced by two lines, and old line 5 was removed.
+        let hunks = [(1, 2, 2), (1, 5, 0)];
+
+        assert_eq!(
+            super::committed_lines(&hunks, 6),
+            [Some(0), None, None, Some(2), Some(3), Some(5)]
+        );
+    }
+
+    #[test]
+    fn unknown_revision_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        Repository::init(dir.path()).unwrap();
+
+        let mut languages = Languages::new();
+        assert!(languages
+            .get_statistics_at_revision(dir.path(), "v1.0", &[], &Config::default())
+            .is_err());
+    }
+}
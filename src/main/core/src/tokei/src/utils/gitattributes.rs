@@ -0,0 +1,280 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use dashmap::DashMap;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::{FileClass, LanguageType};
+
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// The state of an attribute on a line of a `.gitattributes` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum State {
+    Set,
+    Unset,
+    Value(String),
+    Unspecified,
+}
+
+/// A line of a `.gitattributes` file with its linguist attributes.
+#[derive(Debug)]
+struct Rule {
+    pattern: Gitignore,
+    attributes: Vec<(String, State)>,
+}
+
+/// The linguist attributes of a file, as GitHub reads them from the
+/// `.gitattributes` files of its repository.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Linguist {
+    /// `linguist-language`, the language the file is counted as.
+    pub language: Option<LanguageType>,
+    /// `linguist-vendored`, code copied from elsewhere. `Some(false)` when
+    /// unset explicitly, overriding the detected class of the file.
+    pub vendored: Option<bool>,
+    /// `linguist-generated`, code written by a tool. `Some(false)` when
+    /// unset explicitly, overriding the detected class of the file.
+    pub generated: Option<bool>,
+    /// `linguist-documentation`, prose rather than code.
+    pub documentation: bool,
+}
+
+/// The `.gitattributes` files in effect in each directory seen by one walk,
+/// so that every directory and its parents are only read once.
+#[derive(Debug, Default)]
+pub(crate) struct Attributes {
+    dirs: DashMap<PathBuf, Arc<[Arc<Vec<Rule>>]>>,
+}
+
+impl Attributes {
+    /// Reads the attributes of `path` from the `.gitattributes` files of its
+    /// directory and of every parent up to the root of its repository,
+    /// deeper files and later lines taking precedence like in git.
+    pub(crate) fn of(&self, path: &Path) -> Linguist {
+        let path = env::current_dir()
+            .map_or_else(|_| path.to_owned(), |dir| dir.join(path))
+            .components()
+            .collect::<PathBuf>();
+
+        let mut linguist = Linguist::default();
+        let dir = match path.parent() {
+            Some(dir) => dir,
+            None => return linguist,
+        };
+        let files = self.in_dir(dir);
+        let rules = files.iter().flat_map(|rules| rules.iter());
+        for rule in rules.filter(|rule| rule.pattern.matched(&path, false).is_ignore()) {
+            for (name, state) in &rule.attributes {
+                linguist.apply(name, state);
+            }
+        }
+        linguist
+    }
+
+    /// The parsed files in effect in `dir`, outermost first.
+    fn in_dir(&self, dir: &Path) -> Arc<[Arc<Vec<Rule>>]> {
+        if let Some(files) = self.dirs.get(dir) {
+            return files.clone();
+        }
+
+        let mut files = match dir.parent() {
+            Some(parent) if !dir.join(".git").exists() => self.in_dir(parent).to_vec(),
+            _ => Vec::new(),
+        };
+        files.extend(rules_of(dir));
+        let files = Arc::<[_]>::from(files);
+        self.dirs.insert(dir.to_owned(), files.clone());
+        files
+    }
+}
+
+impl Linguist {
+    /// Reads the attributes of a single `path`, see [`Attributes::of`].
+    pub(crate) fn of(path: &Path) -> Self {
+        Attributes::default().of(path)
+    }
+
+    /// The class of the file, `detected` unless its attributes say otherwise.
+    pub(crate) fn class(&self, detected: Option<FileClass>) -> Option<FileClass> {
+        match (self.vendored, self.generated) {
+            (Some(true), _) => Some(FileClass::Vendored),
+            (_, Some(true)) => Some(FileClass::Generated),
+            (vendored, generated) => detected.filter(|class| match class {
+                FileClass::Vendored => vendored.is_none(),
+                FileClass::Generated => generated.is_none(),
+            }),
+        }
+    }
+
+    fn apply(&mut self, name: &str, state: &State) {
+        let enabled = match state {
+            State::Set => true,
+            State::Value(value) => value == "true",
+            State::Unset | State::Unspecified => false,
+        };
+
+        match name {
+            "linguist-vendored" => self.vendored = Some(enabled),
+            "linguist-generated" => self.generated = Some(enabled),
+            "linguist-documentation" => self.documentation = enabled,
+            "linguist-language" => {
+                self.language = match state {
+                    State::Value(name) => LanguageType::from_str(name)
+                        .or_else(|_| LanguageType::from_str(&name.replace('-', " ")))
+                        .ok(),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The parsed `.gitattributes` file of `dir`, if it has one.
+fn rules_of(dir: &Path) -> Option<Arc<Vec<Rule>>> {
+    let text = fs::read_to_string(dir.join(ATTRIBUTES_FILE)).ok()?;
+    Some(Arc::new(parse(dir, &text)))
+}
+
+/// Parses the linguist attributes of a `.gitattributes` file in `dir`,
+/// skipping macros and lines without any.
+fn parse(dir: &Path, text: &str) -> Vec<Rule> {
+    text.lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let pattern = words.next().filter(|p| !p.starts_with(['#', '[']))?;
+            let attributes = words
+                .map(|word| match word.as_bytes()[0] {
+                    b'-' => (&word[1..], State::Unset),
+                    b'!' => (&word[1..], State::Unspecified),
+                    _ => match word.split_once('=') {
+                        Some((name, value)) => (name, State::Value(value.to_owned())),
+                        None => (word, State::Set),
+                    },
+                })
+                .filter(|(name, _)| name.starts_with("linguist-"))
+                .map(|(name, state)| (name.to_owned(), state))
+                .collect::<Vec<_>>();
+            if attributes.is_empty() {
+                return None;
+            }
+
+            let mut builder = GitignoreBuilder::new(dir);
+            match builder.add_line(None, pattern).and_then(|b| b.build()) {
+                Ok(pattern) => Some(Rule {
+                    pattern,
+                    attributes,
+                }),
+                Err(error) => {
+                    error!("{} in {}", error, dir.join(ATTRIBUTES_FILE).display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn repository(attributes: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        for (path, text) in attributes {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn reads_linguist_attributes() {
+        let dir = repository(&[(
+            ".gitattributes",
+            "# GitHub\n*.inc linguist-language=PHP\nvendor/** linguist-vendored\n\
+             docs/** linguist-documentation -diff\n*.pb.go linguist-generated=true\n",
+        )]);
+        let of = |path: &str| Linguist::of(&dir.path().join(path));
+
+        assert_eq!(of("lib/header.inc").language, Some(LanguageType::Php));
+        assert_eq!(of("vendor/lib/a.rs").vendored, Some(true));
+        assert!(of("docs/guide.md").documentation);
+        assert_eq!(of("api/service.pb.go").generated, Some(true));
+        assert_eq!(of("src/main.rs"), Linguist::default());
+    }
+
+    #[test]
+    fn paths_with_current_directories_are_matched() {
+        let dir = repository(&[(".gitattributes", "lib/*.inc linguist-language=PHP\n")]);
+
+        let linguist = Linguist::of(&dir.path().join("./lib/./header.inc"));
+
+        assert_eq!(linguist.language, Some(LanguageType::Php));
+    }
+
+    #[test]
+    fn deeper_files_and_later_lines_take_precedence() {
+        let dir = repository(&[
+            (
+                ".gitattributes",
+                "vendor/** linguist-vendored\n*.h linguist-language=Objective-C\n",
+            ),
+            (
+                "vendor/own/.gitattributes",
+                "*.rs -linguist-vendored\n*.h linguist-language=C++\n",
+            ),
+        ]);
+        let of = |path: &str| Linguist::of(&dir.path().join(path));
+
+        assert_eq!(of("vendor/other/a.rs").vendored, Some(true));
+        assert_eq!(of("vendor/own/a.rs").vendored, Some(false));
+        assert_eq!(of("a.h").language, Some(LanguageType::ObjectiveC));
+        assert_eq!(of("vendor/own/a.h").language, Some(LanguageType::Cpp));
+    }
+
+    #[test]
+    fn directories_are_read_once_per_walk() {
+        let dir = repository(&[("lib/.gitattributes", "*.inc linguist-language=PHP\n")]);
+        let path = dir.path().join("lib/header.inc");
+        let attributes = Attributes::default();
+        assert_eq!(attributes.of(&path).language, Some(LanguageType::Php));
+
+        fs::write(
+            dir.path().join("lib/.gitattributes"),
+            "*.inc linguist-vendored\n",
+        )
+        .unwrap();
+
+        assert_eq!(attributes.of(&path).language, Some(LanguageType::Php));
+        assert_eq!(
+            attributes.of(&dir.path().join("lib/other.inc")).vendored,
+            None
+        );
+        assert_eq!(Attributes::default().of(&path).vendored, Some(true));
+    }
+
+    #[test]
+    fn attributes_override_the_detected_class() {
+        let dir = repository(&[(
+            ".gitattributes",
+            "vendor/** -linguist-vendored\n*.js linguist-generated\n",
+        )]);
+        let of = |path: &str| Linguist::of(&dir.path().join(path));
+
+        assert_eq!(of("vendor/a.rs").class(Some(FileClass::Vendored)), None);
+        assert_eq!(
+            of("vendor/yarn.lock").class(Some(FileClass::Generated)),
+            Some(FileClass::Generated)
+        );
+        assert_eq!(of("app.js").class(None), Some(FileClass::Generated));
+    }
+}
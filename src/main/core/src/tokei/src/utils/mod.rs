@@ -2,3 +2,5 @@
 mod macros;
 pub(crate) mod ext;
 pub mod fs;
+pub mod git;
+pub(crate) mod gitattributes;
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use tokei::{CodeStats, LanguageDiff};
+
+use crate::models::{ModelLangDelta, ModelLangStats, ModelSnapshot, ModelSnapshotDiff};
+
+/// Per-language growth between two snapshots of the same project, `to - from`.
+pub(crate) fn compare_snapshots (from : &ModelSnapshot,to : &ModelSnapshot) -> ModelSnapshotDiff {
+    let older = by_name(&from.languages);
+    let newer = by_name(&to.languages);
+    let mut names = older.keys().chain(newer.keys()).copied().collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+
+    let mut languages : Vec<ModelLangDelta> = Vec::new();
+    for name in names {
+        let diff = LanguageDiff::from_totals(
+            older.get(name).map(|(stats, files, _)| (stats, *files)),
+            newer.get(name).map(|(stats, files, _)| (stats, *files)));
+        // the snapshots are colored with the settings of their project
+        let color = newer.get(name).or_else(|| older.get(name)).map_or("", |(_, _, color)| color);
+        languages.push(ModelLangDelta {
+            name: name.to_string(),
+            color: color.to_string(),
+            change: diff.change.name().to_string(),
+            filesDelta: diff.files,
+            linesDelta: diff.stats.lines(),
+            codeLinesDelta: diff.stats.code,
+            commentLinesDelta: diff.stats.comments,
+            docLinesDelta: diff.stats.docs,
+            blankLinesDelta: diff.stats.blanks,
+        })
+    }
+    // biggest movers first, in either direction
+    languages.sort_by_key(|l| std::cmp::Reverse(l.linesDelta.unsigned_abs()));
+
+    ModelSnapshotDiff {
+        fromSnapshotId: from.id,
+        toSnapshotId: to.id,
+        filesDelta: to.totalFilesCount as isize - from.totalFilesCount as isize,
+        codeLinesDelta: to.totalCodeLinesCount as isize - from.totalCodeLinesCount as isize,
+        commentLinesDelta: to.totalCommentLinesCount as isize - from.totalCommentLinesCount as isize,
+        docLinesDelta: to.totalDocLinesCount as isize - from.totalDocLinesCount as isize,
+        blankLinesDelta: to.totalBlankLinesCount as isize - from.totalBlankLinesCount as isize,
+        sizeOnDiskDelta: to.sizeOnDisk as isize - from.sizeOnDisk as isize,
+        languages
+    }
+}
+
+fn by_name (languages : &[ModelLangStats]) -> BTreeMap<&str, (CodeStats, usize, &str)> {
+    languages.iter().map(|lang| {
+        let mut stats = CodeStats::new();
+        stats.code = lang.codeLinesCount;
+        stats.comments = lang.commentLinesCount;
+        stats.docs = lang.docLinesCount;
+        stats.blanks = lang.blankLinesCount;
+        (lang.name.as_str(), (stats, lang.filesCount, lang.color.as_str()))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lang (name : &str,files : usize,code : usize) -> ModelLangStats {
+        ModelLangStats {
+            name: name.to_string(),
+            color: format!("#{}", name.len()),
+            filesCount: files,
+            totalLinesCount: code + code / 10,
+            codeLinesCount: code,
+            commentLinesCount: 0,
+            docLinesCount: code / 10,
+            blankLinesCount: 0,
+            complexity: 0,
+        }
+    }
+
+    fn snapshot (id : usize,languages : Vec<ModelLangStats>) -> ModelSnapshot {
+        ModelSnapshot {
+            id,
+            projectId: 1,
+            createdAt: id * 1000,
+            totalFilesCount: languages.iter().map(|l| l.filesCount).sum(),
+            totalCodeLinesCount: languages.iter().map(|l| l.codeLinesCount).sum(),
+            totalCommentLinesCount: 0,
+            totalDocLinesCount: languages.iter().map(|l| l.docLinesCount).sum(),
+            totalBlankLinesCount: 0,
+            totalFileTypesCount: languages.len(),
+            sizeOnDisk: 0,
+            languages,
+        }
+    }
+
+    #[test]
+    fn reports_appeared_vanished_and_changed_languages () {
+        let from = snapshot(1,vec![lang("Rust",2,100),lang("C",1,40),lang("TOML",1,10)]);
+        let to = snapshot(2,vec![lang("Rust",3,160),lang("Kotlin",4,20),lang("TOML",1,10)]);
+
+        let diff = compare_snapshots(&from,&to);
+
+        assert_eq!(diff.filesDelta, 4);
+        assert_eq!(diff.codeLinesDelta, 40);
+        assert_eq!(diff.docLinesDelta, 4);
+        let changes = diff.languages.iter().map(|l| (l.name.as_str(), l.change.as_str(), l.codeLinesDelta)).collect::<Vec<_>>();
+        assert_eq!(changes, vec![("Rust", "changed", 60), ("C", "removed", -40), ("Kotlin", "added", 20), ("TOML", "unchanged", 0)]);
+        assert_eq!(diff.languages.iter().map(|l| l.docLinesDelta).collect::<Vec<_>>(), vec![6, -4, 2, 0]);
+        assert_eq!(diff.languages.iter().map(|l| l.color.as_str()).collect::<Vec<_>>(), vec!["#4", "#1", "#6", "#4"]);
+    }
+}
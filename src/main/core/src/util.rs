@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 use fs_extra::dir::get_size;
+use crate::error::CoreResult;
 
 pub(crate) fn get_current_time_millis() -> u128 {
     SystemTime::now()
@@ -9,12 +10,12 @@ pub(crate) fn get_current_time_millis() -> u128 {
         .as_millis()
 }
 
-pub(crate) fn get_sum_of_dirs_sizes (folders : &Vec<String>) -> usize {
+pub(crate) fn get_sum_of_dirs_sizes (folders : &Vec<String>) -> CoreResult<usize> {
     let mut result : usize = 0;
     for folder in folders {
         if Path::new(folder).exists() {
-            result += get_size(folder).unwrap() as usize
+            result += get_size(folder)? as usize
         }
     }
-    result
+    Ok(result)
 }
\ No newline at end of file
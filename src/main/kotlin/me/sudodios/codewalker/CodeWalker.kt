@@ -1,7 +1,9 @@
 package me.sudodios.codewalker
 
+import androidx.compose.foundation.layout.Column
 import androidx.compose.foundation.layout.padding
 import androidx.compose.foundation.layout.size
+import androidx.compose.foundation.layout.width
 import androidx.compose.material.ripple.LocalRippleTheme
 import androidx.compose.material3.CircularProgressIndicator
 import androidx.compose.material3.MaterialTheme
@@ -9,6 +11,7 @@ import androidx.compose.material3.darkColorScheme
 import androidx.compose.runtime.Composable
 import androidx.compose.runtime.CompositionLocalProvider
 import androidx.compose.runtime.SideEffect
+import androidx.compose.ui.Alignment
 import androidx.compose.ui.Modifier
 import androidx.compose.ui.geometry.Size
 import androidx.compose.ui.graphics.StrokeCap
@@ -23,12 +26,16 @@ import androidx.compose.ui.window.application
 import me.sudodios.codewalker.core.Global
 import me.sudodios.codewalker.core.LibCore
 import me.sudodios.codewalker.ui.Routes
+import me.sudodios.codewalker.ui.components.GradientButton
+import me.sudodios.codewalker.ui.components.Txt
 import me.sudodios.codewalker.ui.dialogs.BaseDialog
+import me.sudodios.codewalker.ui.dialogs.ErrorDialog
 import me.sudodios.codewalker.ui.scenes.MainScreen
 import me.sudodios.codewalker.ui.scenes.SplashScreen
 import me.sudodios.codewalker.ui.theme.AppRippleTheme
 import me.sudodios.codewalker.ui.theme.ColorTheme
 import me.sudodios.codewalker.ui.theme.Fonts
+import me.sudodios.codewalker.utils.Utils.formatToPrice
 import moe.tlaster.precompose.PreComposeApp
 import moe.tlaster.precompose.navigation.NavHost
 import moe.tlaster.precompose.navigation.rememberNavigator
@@ -82,10 +89,41 @@ fun App() {
                     expanded = Global.Alert.openLoading.value,
                     backgroundColor = ColorTheme.colorCard2
                 ) {
-                    CircularProgressIndicator(
-                        modifier = Modifier.padding(20.dp).size(48.dp),
-                        strokeCap = StrokeCap.Round,
-                        strokeWidth = 3.dp
+                    Column(horizontalAlignment = Alignment.CenterHorizontally) {
+                        CircularProgressIndicator(
+                            modifier = Modifier.padding(20.dp).size(48.dp),
+                            strokeCap = StrokeCap.Round,
+                            strokeWidth = 3.dp
+                        )
+                        Global.Alert.scanProgress.value?.let {
+                            Txt(
+                                modifier = Modifier.padding(start = 20.dp, end = 20.dp, bottom = 20.dp),
+                                text = "${it.parsedFilesCount.formatToPrice()} / ${it.discoveredFilesCount.formatToPrice()} files - ${it.codeLinesCount.formatToPrice()} lines of code",
+                                color = ColorTheme.colorText.copy(0.8f),
+                                style = MaterialTheme.typography.bodySmall
+                            )
+                            GradientButton(
+                                modifier = Modifier.padding(bottom = 20.dp).width(120.dp),
+                                text = if (it.cancelled) "Cancelling..." else "Cancel",
+                                onClicked = { LibCore.cancelStats() }
+                            )
+                        }
+                    }
+                }
+
+                //scan error dialog
+                BaseDialog(
+                    expanded = Global.Alert.scanError.value != null,
+                    onDismissRequest = {
+                        Global.Alert.scanError.value = null
+                    }
+                ) {
+                    ErrorDialog(
+                        title = "Scan failed",
+                        message = Global.Alert.scanError.value.orEmpty(),
+                        onOKClicked = {
+                            Global.Alert.scanError.value = null
+                        }
                     )
                 }
             }
@@ -0,0 +1,10 @@
+package me.sudodios.codewalker.core
+
+/*
+* thrown by the core lib instead of crashing the jvm
+*/
+
+open class CoreException (message : String) : RuntimeException(message)
+class DbException (message : String) : CoreException(message)
+class IoException (message : String) : CoreException(message)
+class InvalidArgumentException (message : String) : CoreException(message)
@@ -1,6 +1,7 @@
 package me.sudodios.codewalker.core
 
 import androidx.compose.runtime.mutableStateOf
+import me.sudodios.codewalker.models.ModelScanProgress
 
 object Global {
 
@@ -11,6 +12,9 @@ object Global {
     object Alert {
         private var loadingScopes = ArrayList<String>()
         var openLoading = mutableStateOf(false)
+        var scanProgress = mutableStateOf<ModelScanProgress?>(null)
+        /* message of the last scan that failed, shown until dismissed */
+        var scanError = mutableStateOf<String?>(null)
         fun showLoading (key : String) {
             if (!loadingScopes.contains(key)) {
                 loadingScopes.add(key)
@@ -2,26 +2,47 @@ package me.sudodios.codewalker.core
 
 import kotlinx.coroutines.CoroutineScope
 import kotlinx.coroutines.Dispatchers
+import kotlinx.coroutines.delay
 import kotlinx.coroutines.launch
 import me.sudodios.codewalker.models.*
 import java.io.File
 
 object LibCore {
 
+    @Volatile
+    private var currentScan : Long? = null
+    /* handle of the last finished scan, its files can be listed until the next one */
+    @Volatile
+    private var lastResult : Long? = null
+
     /*
     * extern core lib func
     */
 
     external fun version () : String
-    private external fun getDirCodeStats (folders : Array<String>,ignored : Array<String>,hidden : Boolean,noIgnore : Boolean,docAsComm : Boolean) : ModelStatisticsNative
+
+    /*scan*/
+    private external fun startScan (folders : Array<String>,ignored : Array<String>,hidden : Boolean,noIgnore : Boolean,docAsComm : Boolean) : Long
+    private external fun cancelScan (handle : Long) : Boolean
+    private external fun pollScan (handle : Long) : ModelScanProgress?
+    private external fun finishScan (handle : Long) : ModelStatisticsNative
+    private external fun analyzeProject (pId : Long) : Long
+    private external fun releaseScan (handle : Long) : Boolean
+    private external fun listFiles (handle : Long,language : String?,sort : String,offset : Int,limit : Int) : ModelFilePage
+    private external fun directoryTree (handle : Long,depth : Int) : Array<ModelDirStats>
+    private external fun blameScan (handle : Long) : ModelBlame
 
     /*db*/
     external fun initDB (dbPath : String)
+    external fun closeDB ()
     external fun createProject (modelStatisticsDB: ModelStatisticsDB) : Long
     external fun updateProject (modelStatisticsDB: ModelStatisticsDB)
     external fun refreshAnalyze (pId: Long,updateTime : Long,languages : String,totals : String)
     external fun removeProject (pId : Long)
     private external fun readProjects () : Array<ModelStatisticsDB>
+    external fun readSnapshots (pId : Long) : Array<ModelSnapshot>
+    external fun readSnapshot (snapshotId : Long) : ModelSnapshot
+    external fun compareSnapshots (fromId : Long,toId : Long) : ModelSnapshotDiff
 
     fun readProjectsList () : ArrayList<ModelStatistics> {
         val out = ArrayList<ModelStatistics>()
@@ -32,32 +53,91 @@ object LibCore {
     }
 
     fun getStats (projectName : String,projectFolders : ArrayList<String>,ignored : ArrayList<String>,hidden : Boolean,noIgnore : Boolean,docAsComm : Boolean,callback : (ModelStatistics) -> Unit) {
+        projectFolders.removeAll { it.isEmpty() }
+        ignored.removeAll { it.trim().isEmpty() }
+        val configs = Configs(hidden = hidden, no_ignore = noIgnore, doc_as_comment = docAsComm)
+        runScan(projectName, projectFolders, ignored, configs, callback) {
+            startScan(folders = projectFolders.toTypedArray(), ignored = ignored.toTypedArray(), hidden = hidden, noIgnore = noIgnore, docAsComm = docAsComm)
+        }
+    }
+
+    fun analyzeProject (project : ModelStatistics,callback : (ModelStatistics) -> Unit) {
+        runScan(project.name, project.root_folders, project.ignored_folders, project.configs, callback) {
+            analyzeProject(project.id!!)
+        }
+    }
+
+    private fun runScan (projectName : String,projectFolders : ArrayList<String>,ignored : ArrayList<String>,configs : Configs,callback : (ModelStatistics) -> Unit,start : () -> Long) {
         Global.Alert.showLoading("getStats")
         CoroutineScope(Dispatchers.IO).launch {
-            projectFolders.removeAll { it.isEmpty() }
-            ignored.removeAll { it.trim().isEmpty() }
-            val analyze = getDirCodeStats(folders = projectFolders.toTypedArray(), ignored = ignored.toTypedArray(), hidden = hidden, noIgnore = noIgnore, docAsComm = docAsComm)
-            val resultStat = ModelStatistics(
-                name = projectName,
-                root_folders = projectFolders,
-                ignored_folders = ignored,
-                configs = Configs(hidden = hidden, no_ignore = noIgnore, doc_as_comment = docAsComm),
-                last_update = analyze.lastUpdateTime,
-                analyze = ArrayList(analyze.languages.toList()),
-                totals = Totals(
-                    totalFilesCount = analyze.totalFilesCount,
-                    totalCodeLinesCount = analyze.totalCodeLinesCount,
-                    totalCommentLinesCount = analyze.totalCommentLinesCount,
-                    totalBlankLinesCount = analyze.totalBlankLinesCount,
-                    totalFileTypesCount = analyze.totalFileTypesCount,
-                    sizeOnDisk = analyze.sizeOnDisk,
+            var handle : Long? = null
+            try {
+                handle = start()
+                currentScan = handle
+                while (true) {
+                    val progress = pollScan(handle) ?: break
+                    Global.Alert.scanProgress.value = progress
+                    if (progress.finished) break
+                    delay(100)
+                }
+                val analyze = finishScan(handle)
+                if (analyze.incomplete) return@launch
+                lastResult?.let { releaseScan(it) }
+                lastResult = handle
+                val resultStat = ModelStatistics(
+                    name = projectName,
+                    root_folders = projectFolders,
+                    ignored_folders = ignored,
+                    configs = configs,
+                    last_update = analyze.lastUpdateTime,
+                    analyze = ArrayList(analyze.languages.toList()),
+                    totals = Totals(
+                        totalFilesCount = analyze.totalFilesCount,
+                        totalCodeLinesCount = analyze.totalCodeLinesCount,
+                        totalCommentLinesCount = analyze.totalCommentLinesCount,
+                        totalDocLinesCount = analyze.totalDocLinesCount,
+                        totalBlankLinesCount = analyze.totalBlankLinesCount,
+                        totalFileTypesCount = analyze.totalFileTypesCount,
+                        sizeOnDisk = analyze.sizeOnDisk,
+                        categories = ArrayList(analyze.categories.toList()),
+                    )
                 )
-            )
-            Global.Alert.hideLoading("getStats")
-            callback.invoke(resultStat)
+                Global.Alert.scanProgress.value = null
+                Global.Alert.hideLoading("getStats")
+                callback.invoke(resultStat)
+            } catch (e : CoreException) {
+                e.printStackTrace()
+                Global.Alert.scanError.value = e.message ?: e.javaClass.simpleName
+            } finally {
+                /* a scan that failed or was cancelled is dropped, running or not */
+                handle?.takeIf { it != lastResult }?.let { releaseScan(it) }
+                currentScan = null
+                Global.Alert.scanProgress.value = null
+                Global.Alert.hideLoading("getStats")
+            }
         }
     }
 
+    /* files of the last scan, language null for all of them, sort one of code, comments, blanks, lines or files */
+    fun listLastScanFiles (language : String?,sort : String = "code",page : Int = 0,pageSize : Int = 50) : ModelFilePage? {
+        val handle = lastResult ?: return null
+        return listFiles(handle, language, sort, page * pageSize, pageSize)
+    }
+
+    fun lastScanDirectories (depth : Int = 1) : Array<ModelDirStats>? {
+        val handle = lastResult ?: return null
+        return directoryTree(handle, depth)
+    }
+
+    fun lastScanAuthors () : ModelBlame? {
+        val handle = lastResult ?: return null
+        return blameScan(handle)
+    }
+
+    fun cancelStats () {
+        currentScan?.let { cancelScan(it) }
+    }
+
     fun init () : Boolean {
         File(Global.LIB_CORE_PATH).mkdirs()
         val libPath = findLibPath()
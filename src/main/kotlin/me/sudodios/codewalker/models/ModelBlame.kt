@@ -0,0 +1,24 @@
+package me.sudodios.codewalker.models
+
+data class ModelAuthorStats(
+    var name : String = "",
+    var email : String = "",
+    var filesCount : Long = 0,
+    var totalLinesCount : Long = 0,
+    var codeLinesCount : Long = 0,
+    var commentLinesCount : Long = 0,
+    var docLinesCount : Long = 0,
+    var blankLinesCount : Long = 0
+)
+
+data class ModelLangAuthors(
+    var name : String = "",
+    var color : String = "",
+    var authors : Array<ModelAuthorStats> = arrayOf()
+)
+
+data class ModelBlame(
+    var languages : Array<ModelLangAuthors> = arrayOf(),
+    var authors : Array<ModelAuthorStats> = arrayOf(),
+    var skippedFilesCount : Long = 0
+)
@@ -0,0 +1,11 @@
+package me.sudodios.codewalker.models
+
+data class ModelCategoryStats(
+    var name : String = "",
+    var filesCount : Int = 0,
+    var totalLinesCount : Int = 0,
+    var codeLinesCount : Int = 0,
+    var commentLinesCount : Int = 0,
+    var docLinesCount : Int = 0,
+    var blankLinesCount : Int = 0
+)
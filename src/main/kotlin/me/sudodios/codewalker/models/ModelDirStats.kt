@@ -0,0 +1,13 @@
+package me.sudodios.codewalker.models
+
+data class ModelDirStats(
+    var path : String = "",
+    var depth : Int = 0,
+    var filesCount : Long = 0,
+    var totalLinesCount : Long = 0,
+    var codeLinesCount : Long = 0,
+    var commentLinesCount : Long = 0,
+    var docLinesCount : Long = 0,
+    var blankLinesCount : Long = 0,
+    var languages : Array<ModelLangStats> = arrayOf()
+)
@@ -0,0 +1,18 @@
+package me.sudodios.codewalker.models
+
+data class ModelFileStats(
+    var path : String = "",
+    var language : String = "",
+    var totalLinesCount : Long = 0,
+    var codeLinesCount : Long = 0,
+    var commentLinesCount : Long = 0,
+    var docLinesCount : Long = 0,
+    var blankLinesCount : Long = 0,
+    var complexity : Long = 0
+)
+
+data class ModelFilePage(
+    var totalCount : Long = 0,
+    var offset : Long = 0,
+    var files : Array<ModelFileStats> = arrayOf()
+)
@@ -7,5 +7,7 @@ data class ModelLangStats(
     var totalLinesCount : Int = 0,
     var codeLinesCount : Int = 0,
     var commentLinesCount : Int = 0,
-    var blankLinesCount : Int = 0
+    var docLinesCount : Int = 0,
+    var blankLinesCount : Int = 0,
+    var complexity : Int = 0
 )
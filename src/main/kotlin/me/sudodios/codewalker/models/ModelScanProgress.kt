@@ -0,0 +1,12 @@
+package me.sudodios.codewalker.models
+
+data class ModelScanProgress(
+    var discoveredFilesCount : Long = 0,
+    var parsedFilesCount : Long = 0,
+    var currentPath : String = "",
+    var codeLinesCount : Long = 0,
+    var commentLinesCount : Long = 0,
+    var blankLinesCount : Long = 0,
+    var finished : Boolean = false,
+    var cancelled : Boolean = false
+)
@@ -0,0 +1,15 @@
+package me.sudodios.codewalker.models
+
+data class ModelSnapshot(
+    var id : Long = 0,
+    var projectId : Long = 0,
+    var createdAt : Long = 0,
+    var totalFilesCount : Long = 0,
+    var totalCodeLinesCount : Long = 0,
+    var totalCommentLinesCount : Long = 0,
+    var totalDocLinesCount : Long = 0,
+    var totalBlankLinesCount : Long = 0,
+    var totalFileTypesCount : Long = 0,
+    var sizeOnDisk : Long = 0,
+    var languages : Array<ModelLangStats> = arrayOf()
+)
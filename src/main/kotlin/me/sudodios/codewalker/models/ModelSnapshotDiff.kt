@@ -0,0 +1,25 @@
+package me.sudodios.codewalker.models
+
+data class ModelLangDelta(
+    var name : String = "",
+    var color : String = "",
+    var change : String = "",
+    var filesDelta : Long = 0,
+    var linesDelta : Long = 0,
+    var codeLinesDelta : Long = 0,
+    var commentLinesDelta : Long = 0,
+    var docLinesDelta : Long = 0,
+    var blankLinesDelta : Long = 0
+)
+
+data class ModelSnapshotDiff(
+    var fromSnapshotId : Long = 0,
+    var toSnapshotId : Long = 0,
+    var filesDelta : Long = 0,
+    var codeLinesDelta : Long = 0,
+    var commentLinesDelta : Long = 0,
+    var docLinesDelta : Long = 0,
+    var blankLinesDelta : Long = 0,
+    var sizeOnDiskDelta : Long = 0,
+    var languages : Array<ModelLangDelta> = arrayOf()
+)
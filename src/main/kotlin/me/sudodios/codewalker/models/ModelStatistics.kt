@@ -8,11 +8,16 @@ data class ModelStatisticsNative(
     var totalFilesCount : Long = 0,
     var totalCodeLinesCount : Long = 0,
     var totalCommentLinesCount : Long = 0,
+    var totalDocLinesCount : Long = 0,
     var totalBlankLinesCount : Long = 0,
     var totalFileTypesCount : Long = 0,
     var sizeOnDisk : Long = 0,
     var languages : Array<ModelLangStats>,
-    var lastUpdateTime : Long
+    var categories : Array<ModelCategoryStats>,
+    var lastUpdateTime : Long,
+    var incomplete : Boolean = false,
+    /* why the per-file results couldn't be kept for the next scan, null when they were */
+    var cacheError : String? = null
 )
 
 data class ModelStatistics(
@@ -40,16 +45,28 @@ data class ModelStatisticsDB(
 data class Configs (
     var hidden : Boolean = false,
     var no_ignore : Boolean = false,
+    var no_ignore_parent : Boolean = false,
+    var no_ignore_dot : Boolean = false,
+    var no_ignore_vcs : Boolean = false,
     var doc_as_comment : Boolean = false,
+    var types : ArrayList<String>? = null,
+    var categories : ArrayList<String>? = null,
+    var excluded_categories : ArrayList<String>? = null,
+    var sort : String? = null,
+    var excluded : ArrayList<String> = arrayListOf(),
+    var colors : HashMap<String,String> = hashMapOf(),
+    var content_hash : Boolean = false,
 )
 
 data class Totals (
     var totalFilesCount : Long = 0,
     var totalCodeLinesCount : Long = 0,
     var totalCommentLinesCount : Long = 0,
+    var totalDocLinesCount : Long = 0,
     var totalBlankLinesCount : Long = 0,
     var totalFileTypesCount : Long = 0,
-    var sizeOnDisk : Long = 0
+    var sizeOnDisk : Long = 0,
+    var categories : ArrayList<ModelCategoryStats> = arrayListOf()
 )
 
 fun ModelStatistics.forDB () : ModelStatisticsDB {
@@ -24,7 +24,7 @@ fun SortMenu(
     onClicked : (String) -> Unit
 ) {
 
-    listOf("Files","Lines","Codes","Comments","Blanks").forEach {
+    listOf("Files","Lines","Codes","Comments","Docs","Blanks").forEach {
         Row(modifier = Modifier.width(180.dp).background(if (selectedItem == it) ColorTheme.colorText.copy(0.1f) else Color.Transparent).clickable {
             onClicked.invoke(it)
         }.padding(start = 8.dp), verticalAlignment = Alignment.CenterVertically) {
@@ -20,6 +20,7 @@ import androidx.compose.ui.Modifier
 import androidx.compose.ui.draw.clip
 import androidx.compose.ui.res.painterResource
 import androidx.compose.ui.unit.dp
+import me.sudodios.codewalker.core.CoreException
 import me.sudodios.codewalker.core.LibCore
 import me.sudodios.codewalker.models.ModelStatistics
 import me.sudodios.codewalker.models.forDB
@@ -76,7 +77,13 @@ fun CreateProjectDialog(
                 if (modelStatistics.id != null) {
                     //update
                     it.id = modelStatistics.id
-                    LibCore.updateProject(it.forDB())
+                    it.configs = modelStatistics.configs.copy(hidden = searchHidden, no_ignore = noIgnore, doc_as_comment = docAsComm)
+                    try {
+                        LibCore.updateProject(it.forDB())
+                    } catch (e : CoreException) {
+                        e.printStackTrace()
+                        return@getStats
+                    }
                     val index = MainScreen.projectsList.indexOfFirst { d -> d.id == it.id }
                     if (index != -1) {
                         MainScreen.projectsList[index] = it
@@ -86,7 +93,12 @@ fun CreateProjectDialog(
                     }
                 } else {
                     //create
-                    val insertId = LibCore.createProject(it.forDB())
+                    val insertId = try {
+                        LibCore.createProject(it.forDB())
+                    } catch (e : CoreException) {
+                        e.printStackTrace()
+                        return@getStats
+                    }
                     it.id = insertId
                     MainScreen.projectsList.add(it)
                     MainScreen.setViewProject(it)
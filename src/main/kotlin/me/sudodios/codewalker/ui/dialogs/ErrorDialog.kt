@@ -0,0 +1,42 @@
+package me.sudodios.codewalker.ui.dialogs
+
+import androidx.compose.foundation.background
+import androidx.compose.foundation.layout.*
+import androidx.compose.material3.Button
+import androidx.compose.material3.MaterialTheme
+import androidx.compose.runtime.Composable
+import androidx.compose.ui.Modifier
+import androidx.compose.ui.unit.dp
+import me.sudodios.codewalker.ui.components.Txt
+import me.sudodios.codewalker.ui.theme.ColorTheme
+
+@Composable
+fun ErrorDialog(
+    title : String,
+    message : String,
+    onOKClicked : () -> Unit
+) {
+
+    Column(modifier = Modifier.width(340.dp).background(ColorTheme.colorCard2).padding(20.dp)) {
+        Txt(
+            text = title,
+            color = ColorTheme.colorText,
+            style = MaterialTheme.typography.titleMedium
+        )
+        Txt(
+            modifier = Modifier.padding(top = 12.dp, bottom = 16.dp),
+            text = message,
+            color = ColorTheme.colorText.copy(0.7f),
+            style = MaterialTheme.typography.bodyMedium
+        )
+        Button(
+            modifier = Modifier.fillMaxWidth(),
+            onClick = {
+                onOKClicked.invoke()
+            }
+        ) {
+            Txt("OK")
+        }
+    }
+
+}
\ No newline at end of file
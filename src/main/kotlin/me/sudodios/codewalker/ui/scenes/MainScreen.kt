@@ -25,6 +25,7 @@ import androidx.compose.ui.unit.dp
 import kotlinx.coroutines.CoroutineScope
 import kotlinx.coroutines.Dispatchers
 import kotlinx.coroutines.launch
+import me.sudodios.codewalker.core.CoreException
 import me.sudodios.codewalker.core.LibCore
 import me.sudodios.codewalker.models.ModelLangStats
 import me.sudodios.codewalker.models.ModelStatistics
@@ -60,6 +61,7 @@ object MainScreen {
                 totalLinesChart.value = arrayListOf(
                     LanguagesChart("Code lines", color = Color(0xFF807CF8), currentProject.value.totals.totalCodeLinesCount.toInt()),
                     LanguagesChart("Comment lines", color = Color(0xFFEC9966), currentProject.value.totals.totalCommentLinesCount.toInt()),
+                    LanguagesChart("Doc lines", color = Color(0xFF5AB4E6), currentProject.value.totals.totalDocLinesCount.toInt()),
                     LanguagesChart("Blank lines", color = Color(0xFF4CC665), currentProject.value.totals.totalBlankLinesCount.toInt())
                 )
                 totalLinesPerType.value = ArrayList(currentProject.value.analyze.sortedByDescending { it.codeLinesCount }.take(10).map {
@@ -164,16 +166,16 @@ private fun AnalyzeContent(show : Boolean,onEditClicked : () -> Unit) {
                         MyIconButton(
                             icon = "icons/refresh.svg",
                             onClick = {
-                                LibCore.getStats(
-                                    projectName = MainScreen.currentProject.value.name,
-                                    projectFolders = MainScreen.currentProject.value.root_folders,
-                                    ignored = MainScreen.currentProject.value.ignored_folders,
-                                    hidden = MainScreen.currentProject.value.configs.hidden,
-                                    noIgnore = MainScreen.currentProject.value.configs.no_ignore,
-                                    docAsComm = MainScreen.currentProject.value.configs.doc_as_comment,
+                                LibCore.analyzeProject(
+                                    project = MainScreen.currentProject.value,
                                     callback = {
                                         it.id = MainScreen.currentProject.value.id
-                                        LibCore.refreshAnalyze(it.id!!,it.last_update,GsonUtils.gson.toJson(it.analyze),GsonUtils.gson.toJson(it.totals))
+                                        try {
+                                            LibCore.refreshAnalyze(it.id!!,it.last_update,GsonUtils.gson.toJson(it.analyze),GsonUtils.gson.toJson(it.totals))
+                                        } catch (e : CoreException) {
+                                            e.printStackTrace()
+                                            return@analyzeProject
+                                        }
                                         val index = MainScreen.projectsList.indexOfFirst { d -> d.id == it.id }
                                         if (index != -1) {
                                             MainScreen.projectsList[index] = it
@@ -285,6 +287,7 @@ private fun TotalsView() {
             Column(horizontalAlignment = Alignment.CenterHorizontally) {
                 val total = MainScreen.currentProject.value.totals.totalCodeLinesCount +
                         MainScreen.currentProject.value.totals.totalCommentLinesCount +
+                        MainScreen.currentProject.value.totals.totalDocLinesCount +
                         MainScreen.currentProject.value.totals.totalBlankLinesCount
                 AnimatedContent(total) {
                     Txt(
@@ -446,6 +449,12 @@ private fun LanguagesList () {
                 color = ColorTheme.colorPrimary,
                 style = MaterialTheme.typography.labelMedium.copy(textAlign = TextAlign.Start)
             )
+            Txt(
+                modifier = Modifier.weight(1f),
+                text = "Docs",
+                color = ColorTheme.colorPrimary,
+                style = MaterialTheme.typography.labelMedium.copy(textAlign = TextAlign.Start)
+            )
             Txt(
                 modifier = Modifier.weight(1f),
                 text = "Blanks",
@@ -489,6 +498,12 @@ private fun LanguagesList () {
                     color = ColorTheme.colorText,
                     style = MaterialTheme.typography.bodyMedium.copy(textAlign = TextAlign.Start)
                 )
+                Txt(
+                    modifier = Modifier.weight(1f),
+                    text = it.docLinesCount.toLong().formatToPrice(),
+                    color = ColorTheme.colorText,
+                    style = MaterialTheme.typography.bodyMedium.copy(textAlign = TextAlign.Start)
+                )
                 Txt(
                     modifier = Modifier.weight(1f),
                     text = it.blankLinesCount.toLong().formatToPrice(),
@@ -620,6 +635,7 @@ private fun getLanguagesList (sort : String,sortIsDesc : Boolean,filterKey : Str
                 "Lines" -> it.totalLinesCount
                 "Codes" -> it.codeLinesCount
                 "Comments" -> it.commentLinesCount
+                "Docs" -> it.docLinesCount
                 "Blanks" -> it.blankLinesCount
                 else -> it.filesCount
             }
@@ -631,6 +647,7 @@ private fun getLanguagesList (sort : String,sortIsDesc : Boolean,filterKey : Str
                 "Lines" -> it.totalLinesCount
                 "Codes" -> it.codeLinesCount
                 "Comments" -> it.commentLinesCount
+                "Docs" -> it.docLinesCount
                 "Blanks" -> it.blankLinesCount
                 else -> it.filesCount
             }
@@ -23,6 +23,7 @@ import androidx.compose.ui.input.pointer.PointerIcon
 import androidx.compose.ui.input.pointer.pointerHoverIcon
 import androidx.compose.ui.res.painterResource
 import androidx.compose.ui.unit.dp
+import me.sudodios.codewalker.core.CoreException
 import me.sudodios.codewalker.core.LibCore
 import me.sudodios.codewalker.models.ModelStatistics
 import me.sudodios.codewalker.ui.components.*
@@ -225,7 +226,12 @@ private fun ProjectList (projectsList: SnapshotStateList<ModelStatistics>) {
             },
             onOKClicked = {
                 showDelDialog = false
-                LibCore.removeProject(selForDelItem?.id!!)
+                try {
+                    LibCore.removeProject(selForDelItem?.id!!)
+                } catch (e : CoreException) {
+                    e.printStackTrace()
+                    return@DeleteDialog
+                }
                 val index = MainScreen.projectsList.indexOfFirst { it.id == selForDelItem?.id }
                 if (MainScreen.currentProject.value.id == selForDelItem?.id) {
                     MainScreen.setViewProject(null)